@@ -1,7 +1,8 @@
 use libwmgr::actions::Action;
+use libwmgr::actions::ActionOptions;
 use libwmgr::apply_to_focused_window;
 
-fn main() {
+fn parse_action() -> Action {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
         eprintln!("Usage: cli <option>");
@@ -9,10 +10,64 @@ fn main() {
     }
 
     let action_str = &args[1];
-    let action = serde_plain::from_str::<Action>(action_str).unwrap_or_else(|error| {
+    serde_plain::from_str::<Action>(action_str).unwrap_or_else(|error| {
         println!("Invalid command [{}], error [{}]", action_str, error);
         std::process::exit(1);
-    });
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn main() {
+    use libwmgr::backend::macos::MacOsBackend;
+
+    let action = parse_action();
+    apply_to_focused_window(&mut MacOsBackend, action, &ActionOptions::default()).unwrap();
+}
+
+/// Pick a backend according to the environment variables of the session.
+#[cfg(not(target_os = "macos"))]
+fn connect() -> Option<Box<dyn libwmgr::backend::WindowBackend>> {
+    #[cfg(feature = "hyprland")]
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        use libwmgr::backend::hyprland::HyprlandBackend;
+        return Some(Box::new(HyprlandBackend::connect().unwrap()));
+    }
+
+    #[cfg(feature = "sway")]
+    if std::env::var_os("SWAYSOCK").is_some() || std::env::var_os("I3SOCK").is_some() {
+        use libwmgr::backend::sway::SwayBackend;
+        return Some(Box::new(SwayBackend::connect().unwrap()));
+    }
 
-    apply_to_focused_window(action).unwrap();
+    #[cfg(feature = "niri")]
+    if std::env::var_os("NIRI_SOCKET").is_some() {
+        use libwmgr::backend::niri::NiriBackend;
+        return Some(Box::new(NiriBackend::connect().unwrap()));
+    }
+
+    #[cfg(feature = "kwin")]
+    if std::env::var_os("WAYLAND_DISPLAY").is_some()
+        && std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.contains("KDE"))
+    {
+        use libwmgr::backend::kwin::KWinBackend;
+        return Some(Box::new(KWinBackend::connect().unwrap()));
+    }
+
+    #[cfg(feature = "x11")]
+    if std::env::var_os("DISPLAY").is_some() {
+        use libwmgr::backend::x11::X11Backend;
+        return Some(Box::new(X11Backend::connect().unwrap()));
+    }
+
+    None
+}
+
+#[cfg(not(target_os = "macos"))]
+fn main() {
+    let action = parse_action();
+    let Some(mut backend) = connect() else {
+        eprintln!("no backend available, enable the one for your window manager");
+        std::process::exit(1);
+    };
+    apply_to_focused_window(backend.as_mut(), action, &ActionOptions::default()).unwrap();
 }
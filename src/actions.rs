@@ -1,5 +1,17 @@
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::time::Duration;
+
+// `Serialize`/`Deserialize` are implemented by hand below, to write `Grid` as
+// a compact string in human-readable formats.  The derived code, which becomes
+// `Action::serialize()`/`Action::deserialize()` with `remote = "Self"`, handles
+// the other variants, and everything in the other formats.
 #[derive(Debug, Clone, PartialEq, Copy, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self")
+)]
 pub enum Action {
     /// Move the window to fill left half of the screen.
     TopHalf,
@@ -95,11 +107,62 @@ pub enum Action {
     /// Resize window to the top last fourth of the screen.
     TopLastFourth,
 
-    /// Increase the window until it reaches the screen size.
+    /// Split the screen into `columns` × `rows` cells, resize window to the
+    /// `width` × `height` cells whose top-left one is at column `x` and row
+    /// `y`, counting from 0.
+    ///
+    /// With serde, it is written as `grid:<columns>x<rows>:<x>,<y>:<width>x<height>`,
+    /// e.g., `grid:4x3:1,0:2x2`, in human-readable formats like JSON.
+    ///
+    /// NOTE: it does nothing if the cells are not all in the grid.
+    Grid {
+        columns: u32,
+        rows: u32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+
+    /// Place window at `x`, `y` of the screen and resize it to `width` ×
+    /// `height`, e.g., `x: Position::End` puts it against the right edge.
+    Place {
+        x: Position,
+        y: Position,
+        width: Length,
+        height: Length,
+    },
+    /// Resize window to `width` × `height`, keep its center, e.g., 1280 × 720
+    /// pixels for screen recordings.
+    ///
+    /// NOTE: the window is not moved back onto the screen if it no longer
+    /// fits.
+    ResizeTo { width: Length, height: Length },
+
+    /// Increase the window until it reaches the screen size, keep it on the
+    /// screen.
     MakeLarger,
     /// Decrease the window until it reaches its minimal size.
     MakeSmaller,
 
+    /// Move the left edge of window left by a step, see
+    /// [`ActionOptions::resize_step`], the other edges stay.
+    GrowLeft,
+    /// Move the right edge of window right by a step.
+    GrowRight,
+    /// Move the top edge of window up by a step.
+    GrowUp,
+    /// Move the bottom edge of window down by a step.
+    GrowDown,
+    /// Move the left edge of window right by a step, the other edges stay.
+    ShrinkLeft,
+    /// Move the right edge of window left by a step.
+    ShrinkRight,
+    /// Move the top edge of window down by a step.
+    ShrinkUp,
+    /// Move the bottom edge of window up by a step.
+    ShrinkDown,
+
     /// Maximize window to almost fit the screen.
     AlmostMaximize,
     /// Maximize window to fit the screen.
@@ -109,23 +172,43 @@ pub enum Action {
     /// Maximize height of window to fit the screen.
     MaximizeHeight,
 
-    /// Move focused window to the top edge of the screen.
+    /// Move window up by a step, see [`ActionOptions::nudge_step`].
     MoveUp,
-    /// Move focused window to the bottom of the screen.
+    /// Move window down by a step.
     MoveDown,
-    /// Move window to the left edge of the screen.
+    /// Move window left by a step.
     MoveLeft,
-    /// Move window to the right edge of the screen.
+    /// Move window right by a step.
     MoveRight,
 
+    /// Move window to the top edge of the screen, keep its size.
+    MoveToTopEdge,
+    /// Move window to the bottom edge of the screen, keep its size.
+    MoveToBottomEdge,
+    /// Move window to the left edge of the screen, keep its size.
+    MoveToLeftEdge,
+    /// Move window to the right edge of the screen, keep its size.
+    MoveToRightEdge,
+
     /// Move window to the next desktop.
     NextDesktop,
     /// Move window to the previous desktop.
     PreviousDesktop,
-    /// Move window to the next display.
+    /// Move window to the next display, see [`ActionOptions::display_move`].
+    ///
+    /// Displays are ordered by where they are, from left to right, then top
+    /// to bottom.
     NextDisplay,
     /// Move window to the previous display.
     PreviousDisplay,
+    /// Move window to the display on the left.
+    DisplayLeft,
+    /// Move window to the display on the right.
+    DisplayRight,
+    /// Move window to the display above.
+    DisplayAbove,
+    /// Move window to the display below.
+    DisplayBelow,
 
     /// Restore window to its last position.
     Restore,
@@ -133,3 +216,372 @@ pub enum Action {
     /// Toggle fullscreen mode.
     ToggleFullscreen,
 }
+
+/// Options of [`crate::apply_to_focused_window()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ActionOptions {
+    /// How far `MoveUp`, `MoveDown`, `MoveLeft` and `MoveRight` move the
+    /// window, 10 pixels by default.
+    pub nudge_step: Step,
+    /// How much `MakeLarger` and `MakeSmaller` change the window width, the
+    /// height changes proportionally, 20 pixels by default.  Also how far
+    /// `GrowLeft`, `ShrinkLeft` and the like move an edge.
+    pub resize_step: Step,
+    /// How the steps grow when the same action is done to the same window
+    /// again, within `repeat_timeout`, with nothing else moving the window in
+    /// between.
+    pub acceleration: Acceleration,
+    /// How soon a nudge or a resize has to be done again for its step to
+    /// accelerate, 500 milliseconds by default.  Snap sizes cycle however
+    /// late the next press is.
+    pub repeat_timeout: Duration,
+    /// Where `NextDisplay`, `DisplayLeft` and the like put the window on the
+    /// target display.
+    pub display_move: DisplayMove,
+}
+
+impl Default for ActionOptions {
+    fn default() -> Self {
+        Self {
+            nudge_step: Step::Pixels(10.0),
+            resize_step: Step::Pixels(20.0),
+            acceleration: Acceleration::None,
+            repeat_timeout: Duration::from_millis(500),
+            display_move: DisplayMove::Scale,
+        }
+    }
+}
+
+/// The size of a step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Step {
+    /// This many pixels.
+    Pixels(f64),
+    /// This percentage of the screen width, or height for vertical steps,
+    /// e.g., 5.0.
+    Percent(f64),
+}
+
+impl Step {
+    /// Return the step in pixels, along an axis of the screen that is
+    /// `screen_length` long.
+    pub fn pixels(self, screen_length: f64) -> f64 {
+        match self {
+            Step::Pixels(pixels) => pixels,
+            Step::Percent(percent) => screen_length * percent / 100.0,
+        }
+    }
+}
+
+/// How a step grows with repeated presses, so that crossing a big screen
+/// takes fewer of them.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Acceleration {
+    /// The step never changes.
+    #[default]
+    None,
+    /// Every repeat adds `increment` times the step, up to `max` times the
+    /// step.
+    Linear { increment: f64, max: f64 },
+    /// Every repeat multiplies the step by `factor`, up to `max` times the
+    /// step.
+    Exponential { factor: f64, max: f64 },
+}
+
+impl Acceleration {
+    /// Return how many times the step a press moves after `repeat` repeats.
+    pub fn multiplier(self, repeat: usize) -> f64 {
+        // Way more than enough to reach any sane `max`
+        let repeat = repeat.min(1024) as f64;
+
+        // Never go below the step itself
+        match self {
+            Acceleration::None => 1.0,
+            Acceleration::Linear { increment, max } => (1.0 + increment * repeat).min(max).max(1.0),
+            Acceleration::Exponential { factor, max } => factor.powf(repeat).min(max).max(1.0),
+        }
+    }
+}
+
+/// Where a window moved to another display goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisplayMove {
+    /// Keep the window's position and size relative to the visible frame,
+    /// scaled to the target display, e.g., a window on the left half stays on
+    /// the left half.
+    #[default]
+    Scale,
+    /// Fill the visible frame of the target display.
+    Maximize,
+}
+
+/// Where a window edge goes along an axis of the screen, for [`Action::Place`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Position {
+    /// This fraction of the screen from its left or top edge, e.g., 0.5.
+    Fraction(f64),
+    /// This many pixels from the left or top edge of the screen.
+    Pixels(f64),
+    /// Against the left or top edge.
+    Start,
+    /// In the middle.
+    Center,
+    /// Against the right or bottom edge.
+    End,
+}
+
+/// `f64` is not `Hash`, hash its bits, with -0.0 hashed as 0.0 since they
+/// are equal.
+fn hash_f64<H: Hasher>(value: f64, state: &mut H) {
+    let value = if value == 0.0 { 0.0 } else { value };
+    value.to_bits().hash(state);
+}
+
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let Position::Fraction(value) | Position::Pixels(value) = self {
+            hash_f64(*value, state);
+        }
+    }
+}
+
+/// A width or a height, for [`Action::Place`] and [`Action::ResizeTo`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Length {
+    /// This fraction of the screen width or height, e.g., 0.5.
+    Fraction(f64),
+    /// This many pixels.
+    Pixels(f64),
+}
+
+impl Hash for Length {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        let (Length::Fraction(value) | Length::Pixels(value)) = self;
+        hash_f64(*value, state);
+    }
+}
+
+/// Check if the `width` × `height` cells at column `x` and row `y` are in a
+/// `columns` × `rows` grid.
+pub(crate) fn is_valid_grid(
+    columns: u32,
+    rows: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> bool {
+    let fits = |start: u32, length: u32, total: u32| {
+        length > 0 && start.checked_add(length).is_some_and(|end| end <= total)
+    };
+
+    fits(x, width, columns) && fits(y, height, rows)
+}
+
+#[cfg(feature = "serde")]
+const GRID_PREFIX: &str = "grid:";
+
+/// Parse the part of a grid string after [`GRID_PREFIX`], e.g., `4x3:1,0:2x2`.
+#[cfg(feature = "serde")]
+fn parse_grid(spec: &str) -> Option<Action> {
+    fn parse_pair(pair: &str, separator: char) -> Option<(u32, u32)> {
+        let (first, second) = pair.split_once(separator)?;
+        Some((first.parse().ok()?, second.parse().ok()?))
+    }
+
+    let mut parts = spec.split(':');
+    let (columns, rows) = parse_pair(parts.next()?, 'x')?;
+    let (x, y) = parse_pair(parts.next()?, ',')?;
+    let (width, height) = parse_pair(parts.next()?, 'x')?;
+    if parts.next().is_some() || !is_valid_grid(columns, rows, x, y, width, height) {
+        return None;
+    }
+
+    Some(Action::Grid {
+        columns,
+        rows,
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Action {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Action::Grid {
+                columns,
+                rows,
+                x,
+                y,
+                width,
+                height,
+            } if serializer.is_human_readable() => serializer.collect_str(&format_args!(
+                "{GRID_PREFIX}{columns}x{rows}:{x},{y}:{width}x{height}"
+            )),
+            _ => Action::serialize(self, serializer),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Action {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Formats like bincode are not self-describing, they cannot tell
+        // strings from maps, stick to the derived encoding for them.
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ActionVisitor)
+        } else {
+            Action::deserialize(deserializer).and_then(check_grid)
+        }
+    }
+}
+
+/// Reject `action` if it is a `Grid` whose cells are not all in the grid.
+#[cfg(feature = "serde")]
+fn check_grid<E: serde::de::Error>(action: Action) -> Result<Action, E> {
+    match action {
+        Action::Grid {
+            columns,
+            rows,
+            x,
+            y,
+            width,
+            height,
+        } if !is_valid_grid(columns, rows, x, y, width, height) => Err(E::custom(
+            "the cells of the grid action are not all in the grid",
+        )),
+        _ => Ok(action),
+    }
+}
+
+/// Names and grids are strings, actions with parameters like `Place` are
+/// maps.
+#[cfg(feature = "serde")]
+struct ActionVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for ActionVisitor {
+    type Value = Action;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter
+            .write_str("an action name, a grid like grid:4x3:1,0:2x2, or an action with parameters")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Action, E> {
+        use serde::de::value::StrDeserializer;
+        use serde::de::Unexpected;
+
+        match name.strip_prefix(GRID_PREFIX) {
+            Some(spec) => parse_grid(spec).ok_or_else(|| {
+                E::invalid_value(Unexpected::Str(name), &"a grid like grid:4x3:1,0:2x2")
+            }),
+            None => Action::deserialize(StrDeserializer::<E>::new(name)),
+        }
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Action, A::Error> {
+        Action::deserialize(serde::de::value::MapAccessDeserializer::new(map)).and_then(check_grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn signed_zeros_hash_the_same() {
+        assert_eq!(Position::Pixels(0.0), Position::Pixels(-0.0));
+        assert_eq!(
+            hash_of(&Position::Pixels(0.0)),
+            hash_of(&Position::Pixels(-0.0))
+        );
+        assert_eq!(Length::Fraction(0.0), Length::Fraction(-0.0));
+        assert_eq!(
+            hash_of(&Length::Fraction(0.0)),
+            hash_of(&Length::Fraction(-0.0))
+        );
+        assert_ne!(
+            hash_of(&Length::Fraction(0.0)),
+            hash_of(&Length::Pixels(0.0))
+        );
+    }
+
+    /// Actions of every shape: unit, grid and with parameters.
+    #[cfg(feature = "serde")]
+    const ACTIONS: &[Action] = &[
+        Action::LeftHalf,
+        Action::Grid {
+            columns: 4,
+            rows: 3,
+            x: 1,
+            y: 0,
+            width: 2,
+            height: 2,
+        },
+        Action::Place {
+            x: Position::End,
+            y: Position::Fraction(0.5),
+            width: Length::Pixels(1280.0),
+            height: Length::Fraction(0.5),
+        },
+        Action::ResizeTo {
+            width: Length::Pixels(1280.0),
+            height: Length::Pixels(720.0),
+        },
+    ];
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        for action in ACTIONS {
+            let json = serde_json::to_string(action).unwrap();
+            assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), *action);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&ACTIONS[1]).unwrap(),
+            r#""grid:4x3:1,0:2x2""#
+        );
+        assert!(serde_json::from_str::<Action>(r#""grid:4x3:3,0:2x2""#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bincode_round_trip() {
+        for action in ACTIONS {
+            let bytes = bincode::serialize(action).unwrap();
+            assert_eq!(bincode::deserialize::<Action>(&bytes).unwrap(), *action);
+        }
+
+        let invalid = Action::Grid {
+            columns: 4,
+            rows: 3,
+            x: 3,
+            y: 0,
+            width: 2,
+            height: 2,
+        };
+        let bytes = bincode::serialize(&invalid).unwrap();
+        assert!(bincode::deserialize::<Action>(&bytes).is_err());
+    }
+}
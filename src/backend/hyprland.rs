@@ -0,0 +1,523 @@
+//! Hyprland backend.
+//!
+//! It talks to Hyprland over its request socket (`.socket.sock`), windows are
+//! made floating before moving or resizing them, or the tiling layout would
+//! override their frames.
+
+use std::io::Read;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+#[derive(Debug, Deserialize)]
+struct WorkspaceRef {
+    id: i32,
+}
+
+/// Reply of `j/activewindow`, it is `{}` if there is no active window.
+#[derive(Debug, Deserialize)]
+struct ActiveWindow {
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    at: [f64; 2],
+    #[serde(default)]
+    size: [f64; 2],
+    #[serde(default)]
+    floating: bool,
+    workspace: Option<WorkspaceRef>,
+}
+
+/// An element of the `j/clients` reply.
+#[derive(Debug, Deserialize)]
+struct Client {
+    address: String,
+}
+
+/// The active window, in a form that is easy to use.
+#[derive(Debug)]
+struct Window {
+    /// Address in hex, e.g., `0x55d0a7e1f0b0`.
+    address: String,
+    frame: Rect,
+    floating: bool,
+    workspace: i32,
+}
+
+/// An element of the `j/monitors` reply.
+#[derive(Debug, Deserialize)]
+struct Monitor {
+    id: i32,
+    name: String,
+    x: i32,
+    y: i32,
+    /// In physical pixels.
+    width: i32,
+    /// In physical pixels.
+    height: i32,
+    scale: f64,
+    /// A `wl_output` transform, rotations are counter-clockwise.
+    #[serde(default)]
+    transform: i32,
+    /// Space reserved by bars, `[left, top, right, bottom]`.
+    #[serde(default)]
+    reserved: [f64; 4],
+}
+
+impl Monitor {
+    /// The monitor area in the layout (logical) coordinates.
+    fn frame(&self) -> Rect {
+        let mut size = Size::new(
+            self.width as f64 / self.scale,
+            self.height as f64 / self.scale,
+        );
+        // Rotated by 90 or 270 degrees
+        if self.transform % 2 == 1 {
+            size = Size::new(size.height, size.width);
+        }
+
+        Rect::new(Point::new(self.x as f64, self.y as f64), size)
+    }
+
+    fn to_display(&self, is_primary: bool) -> Display {
+        let [left, top, right, bottom] = self.reserved;
+        Display {
+            id: DisplayId::from_name(&self.name),
+            name: self.name.clone(),
+            frame: self.frame(),
+            reserved: Insets::new(top, left, bottom, right),
+            scale_factor: self.scale,
+            // cast is safe, it is in 0..4
+            rotation: (360 - (self.transform.rem_euclid(4) as u32) * 90) % 360,
+            is_primary,
+        }
+    }
+}
+
+/// The Hyprland backend.
+#[derive(Debug, Clone)]
+pub struct HyprlandBackend {
+    socket_path: PathBuf,
+}
+
+impl HyprlandBackend {
+    /// Find the request socket of the running Hyprland instance via
+    /// `$HYPRLAND_INSTANCE_SIGNATURE`.
+    pub fn connect() -> Result<Self, Error> {
+        let signature = std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE")
+            .ok_or_else(|| Error::IpcError("$HYPRLAND_INSTANCE_SIGNATURE is not set".into()))?;
+
+        // Hyprland 0.40+ puts it in $XDG_RUNTIME_DIR, older versions use /tmp
+        let mut candidates = Vec::new();
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir).join("hypr"));
+        }
+        candidates.push(PathBuf::from("/tmp/hypr"));
+
+        let socket_path = candidates
+            .into_iter()
+            .map(|dir| dir.join(&signature).join(".socket.sock"))
+            .find(|path| path.exists())
+            .ok_or_else(|| Error::IpcError("cannot find Hyprland's socket".into()))?;
+
+        Self::connect_to(socket_path)
+    }
+
+    /// Use the request socket at `path`.
+    ///
+    /// Hyprland closes the connection after replying, so a new connection is
+    /// made for every request.
+    pub fn connect_to(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let socket_path = path.as_ref().to_path_buf();
+        // Fail early if the socket does not exist, connecting without sending
+        // a request would make Hyprland log an error.
+        std::fs::metadata(&socket_path)?;
+
+        Ok(Self { socket_path })
+    }
+
+    /// Send a request and return the raw reply.
+    fn request(&self, request: &str) -> Result<String, Error> {
+        let mut stream = UnixStream::connect(&self.socket_path)?;
+        stream.write_all(request.as_bytes())?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+
+        Ok(reply)
+    }
+
+    fn request_json<T: DeserializeOwned>(&self, request: &str) -> Result<T, Error> {
+        let reply = self.request(&format!("j/{}", request))?;
+        serde_json::from_str(&reply).map_err(|error| Error::IpcError(error.to_string()))
+    }
+
+    /// Run the dispatchers in a batch, fail if any of them fails.
+    fn dispatch(&self, dispatchers: &[String]) -> Result<(), Error> {
+        let request = dispatchers
+            .iter()
+            .map(|dispatcher| format!("dispatch {}", dispatcher))
+            .collect::<Vec<_>>()
+            .join(";");
+        let reply = self.request(&format!("[[BATCH]]{}", request))?;
+
+        // Every dispatcher replies "ok" on success
+        match reply
+            .split("\n\n")
+            .map(str::trim)
+            .find(|result| !result.is_empty() && *result != "ok")
+        {
+            Some(error) => Err(Error::IpcError(error.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn active_window(&self) -> Result<Window, Error> {
+        let window: ActiveWindow = self.request_json("activewindow")?;
+        let address = window
+            .address
+            .filter(|address| !address.is_empty())
+            .ok_or(Error::CannotFindFocusWindow)?;
+        let workspace = window.workspace.ok_or(Error::CannotFindFocusWindow)?.id;
+
+        Ok(Window {
+            address,
+            frame: Rect::new(
+                Point::new(window.at[0], window.at[1]),
+                Size::new(window.size[0], window.size[1]),
+            ),
+            floating: window.floating,
+            workspace,
+        })
+    }
+
+    fn monitors(&self) -> Result<Vec<Monitor>, Error> {
+        let mut monitors: Vec<Monitor> = self.request_json("monitors")?;
+        monitors.sort_by_key(|monitor| monitor.id);
+
+        Ok(monitors)
+    }
+
+    /// Parse a window address, e.g., `0x55d0a7e1f0b0`.
+    fn parse_address(address: &str) -> Result<WindowId, Error> {
+        let id = u64::from_str_radix(address.trim_start_matches("0x"), 16)
+            .map_err(|error| Error::IpcError(error.to_string()))?;
+
+        Ok(WindowId(id))
+    }
+
+    /// Dispatchers that make the window floating, if it is not.
+    fn float(window: &Window) -> Option<String> {
+        if window.floating {
+            None
+        } else {
+            Some(format!("setfloating address:{}", window.address))
+        }
+    }
+}
+
+impl WindowBackend for HyprlandBackend {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        Self::parse_address(&self.active_window()?.address)
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        let clients: Vec<Client> = self.request_json("clients")?;
+        clients
+            .iter()
+            .map(|client| Self::parse_address(&client.address))
+            .collect()
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        if !self.list_window_ids()?.contains(&window) {
+            return Err(Error::NoSuchWindow);
+        }
+
+        self.dispatch(&[format!("focuswindow address:0x{:x}", window.0)])
+    }
+
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        Ok(self.active_window()?.frame)
+    }
+
+    /// Hyprland does not expose it.
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        Ok(None)
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        let window = self.active_window()?;
+        let mut dispatchers: Vec<String> = Self::float(&window).into_iter().collect();
+        dispatchers.push(format!(
+            "resizewindowpixel exact {} {},address:{}",
+            frame.size.width.round(),
+            frame.size.height.round(),
+            window.address
+        ));
+        dispatchers.push(format!(
+            "movewindowpixel exact {} {},address:{}",
+            frame.origin.x.round(),
+            frame.origin.y.round(),
+            window.address
+        ));
+
+        self.dispatch(&dispatchers)
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        let window = self.active_window()?;
+        let mut dispatchers: Vec<String> = Self::float(&window).into_iter().collect();
+        dispatchers.push(format!(
+            "movewindowpixel exact {} {},address:{}",
+            origin.x.round(),
+            origin.y.round(),
+            window.address
+        ));
+
+        self.dispatch(&dispatchers)
+    }
+
+    /// Hyprland has no primary monitor, the first one by ID is considered to
+    /// be.
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        Ok(self
+            .monitors()?
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| monitor.to_display(index == 0))
+            .collect())
+    }
+
+    /// Workspaces are created on demand, so there is always a next one.
+    /// `None` if the window is on a special workspace.
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let workspace = self.active_window()?.workspace;
+        // Special workspaces have negative IDs
+        Ok(usize::try_from(workspace).ok().map(|id| id + 1))
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let workspace = self.active_window()?.workspace;
+        Ok(usize::try_from(workspace)
+            .ok()
+            .filter(|id| *id > 1)
+            .map(|id| id - 1))
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        if space == 0 {
+            return Err(Error::NoSuchWorkspace);
+        }
+
+        let window = self.active_window()?;
+        // Unlike movetoworkspacesilent, this follows the window, like what
+        // macOS does.
+        self.dispatch(&[format!(
+            "movetoworkspace {},address:{}",
+            space, window.address
+        )])
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        // Make sure there is an active window, the dispatcher does not take
+        // a window argument.
+        self.active_window()?;
+        // Mode 0 is the real fullscreen, 1 is maximize.
+        self.dispatch(&["fullscreen 0".to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::unix::net::UnixListener;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::backend::test_dir;
+
+    /// Serve every connection to a socket in `dir` like Hyprland: read one
+    /// request, send `reply(request)` and hang up.  Requests are appended to
+    /// the returned log.
+    fn serve(
+        dir: &Path,
+        reply: impl Fn(&str) -> String + Send + 'static,
+    ) -> (PathBuf, Arc<Mutex<Vec<String>>>) {
+        let path = dir.join(".socket.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buffer = [0_u8; 8192];
+                let len = stream.read(&mut buffer).unwrap();
+                let request = String::from_utf8(buffer[..len].to_vec()).unwrap();
+                stream.write_all(reply(&request).as_bytes()).unwrap();
+                log.lock().unwrap().push(request);
+            }
+        });
+
+        (path, requests)
+    }
+
+    /// A tiled active window on workspace 3, and two monitors, `DP-1` with
+    /// bars on every side, and `HDMI-A-1` rotated by 90 degrees.
+    fn reply(request: &str) -> String {
+        match request {
+            "j/activewindow" => r#"{
+                "address": "0x55d0a7e1f0b0", "at": [100, 130], "size": [800, 600],
+                "floating": false, "workspace": { "id": 3, "name": "3" }
+            }"#
+            .to_string(),
+            "j/clients" => {
+                r#"[{ "address": "0x55d0a7e1f0b0" }, { "address": "0xabc" }]"#.to_string()
+            }
+            "j/monitors" => r#"[
+                {
+                    "id": 1, "name": "HDMI-A-1", "x": 1920, "y": 0,
+                    "width": 3840, "height": 2160, "scale": 2.0, "transform": 1,
+                    "reserved": [0, 0, 0, 0]
+                },
+                {
+                    "id": 0, "name": "DP-1", "x": 0, "y": 0,
+                    "width": 1920, "height": 1080, "scale": 1.0, "transform": 0,
+                    "reserved": [10, 30, 20, 40]
+                }
+            ]"#
+            .to_string(),
+            batch if batch.starts_with("[[BATCH]]") => batch
+                .split(';')
+                .map(|dispatcher| {
+                    if dispatcher.contains("fail") {
+                        "Invalid dispatcher\n\n"
+                    } else {
+                        "ok\n\n"
+                    }
+                })
+                .collect(),
+            _ => panic!("unexpected request {}", request),
+        }
+    }
+
+    /// The `[[BATCH]]` requests in `requests`.
+    fn batches(requests: &Mutex<Vec<String>>) -> Vec<String> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.starts_with("[[BATCH]]"))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn dispatchers() {
+        let dir = test_dir("hyprland-dispatchers");
+        let (path, requests) = serve(&dir, reply);
+        let mut backend = HyprlandBackend::connect_to(&path).unwrap();
+
+        backend
+            .set_frontmost_window_frame(Rect::new(Point::new(10.4, 20.6), Size::new(800.0, 599.5)))
+            .unwrap();
+        backend
+            .move_frontmost_window(Point::new(-5.0, 7.0))
+            .unwrap();
+        backend.move_frontmost_window_to_workspace(4).unwrap();
+        backend.toggle_fullscreen().unwrap();
+        backend.focus_window(WindowId(0xabc)).unwrap();
+        assert!(matches!(
+            backend.focus_window(WindowId(0xdef)),
+            Err(Error::NoSuchWindow)
+        ));
+
+        assert_eq!(
+            batches(&requests),
+            [
+                "[[BATCH]]dispatch setfloating address:0x55d0a7e1f0b0;\
+                 dispatch resizewindowpixel exact 800 600,address:0x55d0a7e1f0b0;\
+                 dispatch movewindowpixel exact 10 21,address:0x55d0a7e1f0b0",
+                "[[BATCH]]dispatch setfloating address:0x55d0a7e1f0b0;\
+                 dispatch movewindowpixel exact -5 7,address:0x55d0a7e1f0b0",
+                "[[BATCH]]dispatch movetoworkspace 4,address:0x55d0a7e1f0b0",
+                "[[BATCH]]dispatch fullscreen 0",
+                "[[BATCH]]dispatch focuswindow address:0xabc",
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_dispatcher() {
+        let dir = test_dir("hyprland-failed");
+        let (path, _requests) = serve(&dir, |request| {
+            reply(&request.replace("fullscreen", "fail"))
+        });
+        let mut backend = HyprlandBackend::connect_to(&path).unwrap();
+
+        assert!(matches!(
+            backend.toggle_fullscreen(),
+            Err(Error::IpcError(error)) if error == "Invalid dispatcher"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn displays() {
+        let dir = test_dir("hyprland-displays");
+        let (path, _requests) = serve(&dir, reply);
+        let backend = HyprlandBackend::connect_to(&path).unwrap();
+
+        let displays = backend.list_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+
+        // Sorted by ID, `reserved` is `[left, top, right, bottom]`
+        assert_eq!(displays[0].name, "DP-1");
+        assert!(displays[0].is_primary);
+        assert_eq!(displays[0].reserved, Insets::new(30.0, 10.0, 40.0, 20.0));
+        assert_eq!(
+            displays[0].visible_frame(),
+            Rect::new(Point::new(10.0, 30.0), Size::new(1890.0, 1010.0))
+        );
+
+        // Logical size, rotated, and counter-clockwise turned clockwise
+        assert_eq!(displays[1].name, "HDMI-A-1");
+        assert!(!displays[1].is_primary);
+        assert_eq!(
+            displays[1].frame,
+            Rect::new(Point::new(1920.0, 0.0), Size::new(1080.0, 1920.0))
+        );
+        assert_eq!(displays[1].scale_factor, 2.0);
+        assert_eq!(displays[1].rotation, 270);
+
+        assert_eq!(
+            backend.get_active_display_id().unwrap(),
+            DisplayId::from_name("DP-1")
+        );
+        // Around workspace 3
+        assert_eq!(backend.get_next_workspace_logical_id().unwrap(), Some(4));
+        assert_eq!(
+            backend.get_previous_workspace_logical_id().unwrap(),
+            Some(2)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+// The KWin script run by the KWin backend, see `kwin.rs`.
+//
+// The backend prepends the `request`, `replyService`, `replyPath` and
+// `replyInterface` constants.  A script cannot return values to whoever
+// loaded it, so the result is sent back with `callDBus()`.
+//
+// Written against the KWin 6 scripting API.
+
+function rect(r) {
+    return { x: r.x, y: r.y, width: r.width, height: r.height };
+}
+
+// The window ID used by the backend: the UUID folded into 64 bits, as two
+// 32-bit halves since there are no 64-bit integers here.
+function foldedId(window) {
+    const hex = window.internalId.toString().replace(/[{}-]/g, "");
+    const part = (i) => parseInt(hex.slice(i * 8, i * 8 + 8), 16);
+    return [(part(0) ^ part(2)) >>> 0, (part(1) ^ part(3)) >>> 0];
+}
+
+function desktopIndex(desktop) {
+    return workspace.desktops.findIndex((d) => d.id === desktop.id);
+}
+
+function state() {
+    const window = workspace.activeWindow;
+    const minSize = window && window.minSize;
+
+    return {
+        window: window
+            ? {
+                  id: window.internalId.toString(),
+                  frame: rect(window.frameGeometry),
+                  min_size: minSize ? { width: minSize.width, height: minSize.height } : null,
+              }
+            : null,
+        screens: workspace.screens.map((output) => ({
+            name: output.name,
+            frame: rect(output.geometry),
+            visible_frame: rect(
+                workspace.clientArea(KWin.MaximizeArea, output, workspace.currentDesktop)
+            ),
+            scale_factor: output.devicePixelRatio,
+        })),
+        current_desktop: desktopIndex(workspace.currentDesktop),
+        desktop_count: workspace.desktops.length,
+    };
+}
+
+function run() {
+    if (request.op === "state") {
+        return state();
+    }
+    if (request.op === "windows") {
+        return workspace.windowList().map((window) => window.internalId.toString());
+    }
+    if (request.op === "focus") {
+        const window = workspace.windowList().find((window) => {
+            const id = foldedId(window);
+            return id[0] === request.id[0] && id[1] === request.id[1];
+        });
+        if (!window) {
+            return { error: "no such window" };
+        }
+        workspace.activeWindow = window;
+        return null;
+    }
+
+    const window = workspace.activeWindow;
+    if (!window) {
+        return { error: "no active window" };
+    }
+
+    switch (request.op) {
+        case "set_frame": {
+            const frame = request.frame;
+            // A maximized window would ignore the new geometry
+            window.setMaximize(false, false);
+            window.frameGeometry = Qt.rect(frame.x, frame.y, frame.width, frame.height);
+            return null;
+        }
+        case "move": {
+            const size = window.frameGeometry;
+            window.frameGeometry = Qt.rect(
+                request.origin.x,
+                request.origin.y,
+                size.width,
+                size.height
+            );
+            return null;
+        }
+        case "move_to_desktop": {
+            const desktop = workspace.desktops[request.desktop];
+            if (!desktop) {
+                return { error: "no such desktop" };
+            }
+            window.desktops = [desktop];
+            // Follow the window, like what macOS does
+            workspace.currentDesktop = desktop;
+            workspace.activeWindow = window;
+            return null;
+        }
+        case "toggle_fullscreen":
+            window.fullScreen = !window.fullScreen;
+            return null;
+        default:
+            return { error: "unknown request " + request.op };
+    }
+}
+
+let result;
+try {
+    result = run();
+} catch (error) {
+    result = { error: String(error) };
+}
+callDBus(
+    replyService,
+    replyPath,
+    replyInterface,
+    "Reply",
+    JSON.stringify({ serial: request.serial, result: result })
+);
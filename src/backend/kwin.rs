@@ -0,0 +1,719 @@
+//! KWin backend.
+//!
+//! On Wayland, KWin does not let other clients move windows, but it can run
+//! scripts.  For every request, this backend loads a small [KWin script]
+//! through the `org.kde.kwin.Scripting` D-Bus interface, runs it, and waits
+//! for the script to send the result back by calling a D-Bus object exported
+//! by this backend.  The scripts are written to new files, with random names,
+//! that only the user can access.
+//!
+//! [KWin script]: https://develop.kde.org/docs/plasma/kwin/
+
+use std::collections::hash_map::RandomState;
+use std::fs::OpenOptions;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+use zbus::blocking::Connection;
+
+use crate::backend::find_active_display;
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::FrontmostWindow;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+use crate::plan::ScreenContext;
+
+const KWIN_SERVICE: &str = "org.kde.KWin";
+const SCRIPTING_PATH: &str = "/Scripting";
+const SCRIPTING_INTERFACE: &str = "org.kde.kwin.Scripting";
+const SCRIPT_INTERFACE: &str = "org.kde.kwin.Script";
+
+/// Where scripts send their results to.
+const REPLY_PATH: &str = "/org/libwmgr/KWin";
+const REPLY_INTERFACE: &str = "org.libwmgr.KWinScript";
+
+/// How long to wait for a script to reply.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Errors reported by the script that have a dedicated [`Error`] variant.
+const NO_ACTIVE_WINDOW: &str = "no active window";
+const NO_SUCH_DESKTOP: &str = "no such desktop";
+const NO_SUCH_WINDOW: &str = "no such window";
+
+const SCRIPT: &str = include_str!("kwin.js");
+
+/// Serial of the last request, used to tell replies apart, shared by all
+/// backends of this process.
+static SERIAL: AtomicU64 = AtomicU64::new(0);
+
+/// The D-Bus object that scripts call.
+struct ScriptReplies {
+    sender: mpsc::Sender<String>,
+}
+
+#[zbus::interface(name = "org.libwmgr.KWinScript")]
+impl ScriptReplies {
+    fn reply(&self, json: String) {
+        // The backend is gone if this fails, nobody cares about the reply.
+        let _ = self.sender.send(json);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Reply {
+    serial: u64,
+    result: Value,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct ScriptRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl From<ScriptRect> for Rect {
+    fn from(rect: ScriptRect) -> Self {
+        Rect::new(
+            Point::new(rect.x, rect.y),
+            Size::new(rect.width, rect.height),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct ScriptSize {
+    width: f64,
+    height: f64,
+}
+
+impl From<ScriptSize> for Size {
+    fn from(size: ScriptSize) -> Self {
+        Size::new(size.width, size.height)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScriptWindow {
+    /// `internalId`, a UUID.
+    id: String,
+    frame: ScriptRect,
+    /// `minSize`, `None` if KWin does not know.
+    min_size: Option<ScriptSize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScriptScreen {
+    /// The connector name, e.g., `DP-1`.
+    name: String,
+    frame: ScriptRect,
+    visible_frame: ScriptRect,
+    scale_factor: f64,
+}
+
+/// Result of the `state` request.
+#[derive(Debug, Deserialize)]
+struct State {
+    window: Option<ScriptWindow>,
+    screens: Vec<ScriptScreen>,
+    /// Index of the current desktop, starts from 0.
+    current_desktop: i32,
+    desktop_count: usize,
+}
+
+impl State {
+    fn window(&self) -> Result<&ScriptWindow, Error> {
+        self.window.as_ref().ok_or(Error::CannotFindFocusWindow)
+    }
+
+    /// Logical ID of the current desktop, `None` if KWin does not know.
+    fn current_desktop(&self) -> Option<usize> {
+        usize::try_from(self.current_desktop)
+            .ok()
+            .map(|index| index + 1)
+    }
+}
+
+/// The KWin backend.
+#[derive(Debug)]
+pub struct KWinBackend {
+    connection: Connection,
+    replies: mpsc::Receiver<String>,
+    /// Where the scripts are written to, see [`script_dir()`].
+    script_dir: PathBuf,
+}
+
+impl KWinBackend {
+    /// Connect to KWin on the session bus.
+    pub fn connect() -> Result<Self, Error> {
+        Self::with_connection(Connection::session()?)
+    }
+
+    /// Connect to KWin on the bus at `address`, e.g., `unix:path=/run/bus`.
+    pub fn connect_to(address: &str) -> Result<Self, Error> {
+        let connection = zbus::blocking::connection::Builder::address(address)?.build()?;
+        Self::with_connection(connection)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, Error> {
+        let (sender, replies) = mpsc::channel();
+        connection
+            .object_server()
+            .at(REPLY_PATH, ScriptReplies { sender })?;
+
+        Ok(Self {
+            connection,
+            replies,
+            script_dir: script_dir(),
+        })
+    }
+
+    /// Run the script with `request`, and deserialize its result.
+    fn request<T: DeserializeOwned>(&self, mut request: Value) -> Result<T, Error> {
+        let serial = SERIAL.fetch_add(1, Ordering::Relaxed) + 1;
+        request["serial"] = serial.into();
+
+        let reply_service = self
+            .connection
+            .unique_name()
+            .ok_or_else(|| Error::IpcError("not connected to a message bus".into()))?
+            .to_string();
+        let script = format!(
+            "const request = {};\nconst replyService = {};\nconst replyPath = {};\nconst replyInterface = {};\n{}",
+            request,
+            Value::from(reply_service),
+            Value::from(REPLY_PATH),
+            Value::from(REPLY_INTERFACE),
+            SCRIPT,
+        );
+
+        // KWin only loads scripts from files
+        let plugin_name = random_plugin_name(serial);
+        let path = self.script_dir.join(format!("{}.js", plugin_name));
+        write_new_file(&path, &script)?;
+        let result = self.run_script(&path.to_string_lossy(), &plugin_name, serial);
+        // Cleanup failures are not worth reporting
+        let _ = self.call_scripting::<bool>("unloadScript", &(plugin_name.as_str(),));
+        let _ = std::fs::remove_file(&path);
+        let result = result?;
+
+        if let Some(error) = result.get("error").and_then(Value::as_str) {
+            return Err(match error {
+                NO_ACTIVE_WINDOW => Error::CannotFindFocusWindow,
+                NO_SUCH_DESKTOP => Error::NoSuchWorkspace,
+                NO_SUCH_WINDOW => Error::NoSuchWindow,
+                _ => Error::IpcError(error.to_string()),
+            });
+        }
+
+        serde_json::from_value(result).map_err(|error| Error::IpcError(error.to_string()))
+    }
+
+    /// Load and run the script at `path`, return its result.
+    fn run_script(&self, path: &str, plugin_name: &str, serial: u64) -> Result<Value, Error> {
+        let id: i32 = self.call_scripting("loadScript", &(path, plugin_name))?;
+        if id < 0 {
+            return Err(Error::IpcError(format!("KWin cannot load script {}", path)));
+        }
+        self.connection.call_method(
+            Some(KWIN_SERVICE),
+            format!("{}/Script{}", SCRIPTING_PATH, id).as_str(),
+            Some(SCRIPT_INTERFACE),
+            "run",
+            &(),
+        )?;
+
+        // Scripts run asynchronously, skip replies of earlier scripts that
+        // timed out.
+        loop {
+            let reply = self
+                .replies
+                .recv_timeout(REPLY_TIMEOUT)
+                .map_err(|_| Error::IpcError("KWin script did not reply".into()))?;
+            let reply: Reply =
+                serde_json::from_str(&reply).map_err(|error| Error::IpcError(error.to_string()))?;
+            if reply.serial == serial {
+                return Ok(reply.result);
+            }
+        }
+    }
+
+    fn call_scripting<R: DeserializeOwned + zbus::zvariant::Type>(
+        &self,
+        method: &str,
+        body: &(impl serde::Serialize + zbus::zvariant::DynamicType),
+    ) -> Result<R, Error> {
+        let reply = self.connection.call_method(
+            Some(KWIN_SERVICE),
+            SCRIPTING_PATH,
+            Some(SCRIPTING_INTERFACE),
+            method,
+            body,
+        )?;
+
+        Ok(reply.body().deserialize()?)
+    }
+
+    fn state(&self) -> Result<State, Error> {
+        self.request(json!({ "op": "state" }))
+    }
+
+    fn displays(screens: Vec<ScriptScreen>) -> Vec<Display> {
+        screens
+            .into_iter()
+            .enumerate()
+            .map(|(index, screen)| Display {
+                id: DisplayId::from_name(&screen.name),
+                name: screen.name,
+                frame: screen.frame.into(),
+                reserved: Insets::between(&screen.frame.into(), &screen.visible_frame.into()),
+                scale_factor: screen.scale_factor,
+                rotation: 0,
+                is_primary: index == 0,
+            })
+            .collect()
+    }
+
+    /// Fold the UUID of a window, i.e., its `internalId`, into a [`WindowId`].
+    fn parse_uuid(uuid: &str) -> Result<WindowId, Error> {
+        let uuid = uuid.replace(['{', '}', '-'], "");
+        let uuid =
+            u128::from_str_radix(&uuid, 16).map_err(|error| Error::IpcError(error.to_string()))?;
+
+        Ok(WindowId((uuid >> 64) as u64 ^ uuid as u64))
+    }
+}
+
+/// Return the directory to write scripts to, `$XDG_RUNTIME_DIR` which only the
+/// user can access, or the temporary directory if it is not set.
+fn script_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Return an unpredictable name for the script of request `serial`, so that
+/// nobody can put a file at its path beforehand.
+fn random_plugin_name(serial: u64) -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_u64(serial);
+    if let Ok(elapsed) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+
+    format!("libwmgr-{:016x}", hasher.finish())
+}
+
+/// Write `contents` to a new file at `path` that only the user can read and
+/// write, fail if something is already there, rather than following it.
+fn write_new_file(path: &Path, contents: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    if let Err(error) = file.write_all(contents.as_bytes()) {
+        let _ = std::fs::remove_file(path);
+        return Err(error.into());
+    }
+
+    Ok(())
+}
+
+impl WindowBackend for KWinBackend {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        Self::parse_uuid(&self.state()?.window()?.id)
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        let uuids: Vec<String> = self.request(json!({ "op": "windows" }))?;
+        uuids.iter().map(|uuid| Self::parse_uuid(uuid)).collect()
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        // JavaScript numbers cannot hold 64-bit integers
+        let id = [window.0 >> 32, window.0 & u64::from(u32::MAX)];
+        self.request(json!({ "op": "focus", "id": id }))
+    }
+
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        Ok(self.state()?.window()?.frame.into())
+    }
+
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        Ok(self.state()?.window()?.min_size.map(Size::from))
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        self.request(json!({
+            "op": "set_frame",
+            "frame": {
+                "x": frame.origin.x,
+                "y": frame.origin.y,
+                "width": frame.size.width,
+                "height": frame.size.height,
+            },
+        }))
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        self.request(json!({
+            "op": "move",
+            "origin": { "x": origin.x, "y": origin.y },
+        }))
+    }
+
+    /// KWin does not tell which screen is the primary one or how screens are
+    /// rotated, the first screen is considered to be the primary one.
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        Ok(Self::displays(self.state()?.screens))
+    }
+
+    /// Same as the default, but from a single script run.
+    fn get_active_display_id(&self) -> Result<DisplayId, Error> {
+        Ok(self.get_frontmost_window()?.screens.active_display)
+    }
+
+    /// Same as the default, but from a single script run.
+    fn get_frontmost_window(&self) -> Result<FrontmostWindow, Error> {
+        let state = self.state()?;
+        let window = state.window()?;
+        let frame = Rect::from(window.frame);
+        let min_size = window.min_size.map(Size::from);
+        let displays = Self::displays(state.screens);
+        let active_display = find_active_display(&displays, frame)
+            .map(|display| display.id)
+            .ok_or(Error::NoDisplay)?;
+
+        Ok(FrontmostWindow {
+            screens: ScreenContext {
+                active_display,
+                displays,
+            },
+            frame,
+            min_size,
+        })
+    }
+
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let state = self.state()?;
+        Ok(state
+            .current_desktop()
+            .filter(|desktop| *desktop < state.desktop_count)
+            .map(|desktop| desktop + 1))
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let state = self.state()?;
+        Ok(state
+            .current_desktop()
+            .filter(|desktop| *desktop > 1)
+            .map(|desktop| desktop - 1))
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        if space == 0 {
+            return Err(Error::NoSuchWorkspace);
+        }
+
+        self.request(json!({ "op": "move_to_desktop", "desktop": space - 1 }))
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        self.request(json!({ "op": "toggle_fullscreen" }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Child;
+    use std::process::Command;
+    use std::process::Stdio;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::actions::Action;
+    use crate::actions::ActionOptions;
+    use crate::apply_to_focused_window;
+    use crate::backend::test_dir;
+
+    /// What the mock KWin saw.
+    #[derive(Debug, Default)]
+    struct Log {
+        /// Path, permission bits and plugin name of the loaded scripts.
+        loaded: Vec<(PathBuf, u32, String)>,
+        unloaded: Vec<String>,
+        requests: Vec<Value>,
+    }
+
+    /// A mock of `org.kde.kwin.Scripting`, the "scripts" it runs reply with
+    /// canned results.
+    struct MockScripting {
+        log: Arc<Mutex<Log>>,
+    }
+
+    #[zbus::interface(name = "org.kde.kwin.Scripting")]
+    impl MockScripting {
+        #[zbus(name = "loadScript")]
+        async fn load_script(
+            &self,
+            path: String,
+            plugin_name: String,
+            #[zbus(object_server)] server: &zbus::ObjectServer,
+        ) -> i32 {
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+            let id = {
+                let mut log = self.log.lock().unwrap();
+                log.loaded.push((PathBuf::from(&path), mode, plugin_name));
+                log.loaded.len() as i32
+            };
+            let script = MockScript {
+                source: std::fs::read_to_string(&path).unwrap(),
+                log: self.log.clone(),
+            };
+            server
+                .at(format!("{}/Script{}", SCRIPTING_PATH, id), script)
+                .await
+                .unwrap();
+
+            id
+        }
+
+        #[zbus(name = "unloadScript")]
+        fn unload_script(&self, plugin_name: String) -> bool {
+            self.log.lock().unwrap().unloaded.push(plugin_name);
+            true
+        }
+    }
+
+    struct MockScript {
+        source: String,
+        log: Arc<Mutex<Log>>,
+    }
+
+    impl MockScript {
+        /// The value of `const name = ...;` prepended by the backend.
+        fn constant(&self, name: &str) -> Value {
+            let prefix = format!("const {} = ", name);
+            let line = self
+                .source
+                .lines()
+                .find_map(|line| line.strip_prefix(&prefix))
+                .unwrap();
+            serde_json::from_str(line.trim_end_matches(';')).unwrap()
+        }
+    }
+
+    #[zbus::interface(name = "org.kde.kwin.Script")]
+    impl MockScript {
+        #[zbus(name = "run")]
+        async fn run(&self, #[zbus(connection)] connection: &zbus::Connection) {
+            let request = self.constant("request");
+            let result = match request["op"].as_str().unwrap() {
+                "state" => json!({
+                    "window": {
+                        "id": "{0b5a3c1e-1111-2222-3333-444455556666}",
+                        "frame": { "x": 100, "y": 130, "width": 800, "height": 600 },
+                        "min_size": null,
+                    },
+                    "screens": [{
+                        "name": "DP-1",
+                        "frame": { "x": 0, "y": 0, "width": 1920, "height": 1080 },
+                        "visible_frame": { "x": 0, "y": 0, "width": 1920, "height": 1040 },
+                        "scale_factor": 1.25,
+                    }],
+                    "current_desktop": 0,
+                    "desktop_count": 2,
+                }),
+                _ => Value::Null,
+            };
+            let reply = json!({ "serial": request["serial"], "result": result });
+            self.log.lock().unwrap().requests.push(request);
+
+            // Like KWin, reply after `run` returns
+            let destination = self.constant("replyService").as_str().unwrap().to_string();
+            let connection = Connection::from(connection.clone());
+            std::thread::spawn(move || {
+                connection
+                    .call_method(
+                        Some(destination.as_str()),
+                        REPLY_PATH,
+                        Some(REPLY_INTERFACE),
+                        "Reply",
+                        &(reply.to_string(),),
+                    )
+                    .unwrap();
+            });
+        }
+    }
+
+    /// A private message bus, killed on drop.
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Bus {
+        /// Start one in `dir`, `dbus-daemon` has to be installed.
+        fn start(dir: &Path) -> Self {
+            let config = dir.join("bus.conf");
+            std::fs::write(
+                &config,
+                format!(
+                    r#"<busconfig>
+  <type>session</type>
+  <listen>unix:path={}</listen>
+  <policy context="default">
+    <allow send_destination="*"/>
+    <allow receive_sender="*"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>"#,
+                    dir.join("bus").display()
+                ),
+            )
+            .unwrap();
+
+            let mut daemon = Command::new("dbus-daemon")
+                .arg(format!("--config-file={}", config.display()))
+                .args(["--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("dbus-daemon is needed to test the KWin backend");
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+
+            Self {
+                daemon,
+                address: address.trim().to_string(),
+            }
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    #[test]
+    fn scripts_over_a_private_bus() {
+        let dir = test_dir("kwin-bus");
+        let bus = Bus::start(&dir);
+        let log = Arc::new(Mutex::new(Log::default()));
+        let _kwin = zbus::blocking::connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name(KWIN_SERVICE)
+            .unwrap()
+            .serve_at(SCRIPTING_PATH, MockScripting { log: log.clone() })
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut backend = KWinBackend::connect_to(&bus.address).unwrap();
+        let script_dir = dir.join("runtime");
+        std::fs::create_dir(&script_dir).unwrap();
+        backend.script_dir = script_dir.clone();
+
+        assert_eq!(
+            backend.get_frontmost_window_frame().unwrap(),
+            Rect::new(Point::new(100.0, 130.0), Size::new(800.0, 600.0))
+        );
+        let displays = backend.list_displays().unwrap();
+        assert_eq!(displays.len(), 1);
+        assert_eq!(displays[0].id, DisplayId::from_name("DP-1"));
+        assert_eq!(displays[0].reserved, Insets::new(0.0, 0.0, 40.0, 0.0));
+        assert_eq!(displays[0].scale_factor, 1.25);
+        backend.move_frontmost_window_to_workspace(2).unwrap();
+        // On the first of 2 desktops
+        assert_eq!(backend.get_next_workspace_logical_id().unwrap(), Some(2));
+        assert_eq!(backend.get_previous_workspace_logical_id().unwrap(), None);
+
+        // Everything needed to plan it is read by one script
+        apply_to_focused_window(&mut backend, Action::LeftHalf, &ActionOptions::default()).unwrap();
+
+        let log = log.lock().unwrap();
+        let ops: Vec<&str> = log.requests[5..]
+            .iter()
+            .map(|request| request["op"].as_str().unwrap())
+            .collect();
+        // The window ID, what is needed to plan, the frame set and read back
+        assert_eq!(ops, ["state", "state", "set_frame", "state"]);
+        assert_eq!(
+            log.requests[7]["frame"],
+            json!({ "x": 0.0, "y": 0.0, "width": 960.0, "height": 1040.0 })
+        );
+        assert_eq!(log.requests[2]["op"], "move_to_desktop");
+        assert_eq!(log.requests[2]["desktop"], 1);
+
+        assert_eq!(log.loaded.len(), 9);
+        for (path, mode, plugin_name) in &log.loaded {
+            assert_eq!(path.parent(), Some(script_dir.as_path()));
+            assert_eq!(*mode, 0o600);
+            assert!(!path.exists(), "{} is left behind", path.display());
+            assert!(log.unloaded.contains(plugin_name));
+        }
+        // Not guessable from the PID and the serial
+        assert_ne!(log.loaded[0].2, log.loaded[1].2);
+        assert!(!log.loaded[0].2.contains(&std::process::id().to_string()));
+
+        drop(bus);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn never_follow_an_existing_file() {
+        let dir = test_dir("kwin-file");
+        let existing = dir.join("existing.js");
+        let target = dir.join("target");
+        std::fs::write(&existing, "planted").unwrap();
+        std::fs::write(&target, "precious").unwrap();
+        std::os::unix::fs::symlink(&target, dir.join("link.js")).unwrap();
+
+        assert!(write_new_file(&existing, "script").is_err());
+        assert!(write_new_file(&dir.join("link.js"), "script").is_err());
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "planted");
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "precious");
+
+        let new = dir.join("new.js");
+        write_new_file(&new, "script").unwrap();
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "script");
+        assert_eq!(
+            std::fs::metadata(&new).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
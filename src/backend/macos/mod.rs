@@ -0,0 +1,867 @@
+mod private;
+
+use std::ffi::c_uint;
+use std::ffi::c_ushort;
+use std::ffi::c_void;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+use objc2::MainThreadMarker;
+use objc2_app_kit::NSEvent;
+use objc2_app_kit::NSRunningApplication;
+use objc2_app_kit::NSScreen;
+use objc2_app_kit::NSStatusBar;
+use objc2_app_kit::NSWorkspace;
+use objc2_application_services::AXError;
+use objc2_application_services::AXUIElement;
+use objc2_application_services::AXValue;
+use objc2_application_services::AXValueType;
+use objc2_core_foundation::CFBoolean;
+use objc2_core_foundation::CFRetained;
+use objc2_core_foundation::CFString;
+use objc2_core_foundation::CFType;
+use objc2_core_foundation::CGPoint;
+use objc2_core_foundation::CGRect;
+use objc2_core_foundation::CGSize;
+use objc2_core_foundation::Type;
+use objc2_core_foundation::{CFArray, CFDictionary, CFNumber};
+use objc2_core_graphics::kCGNullWindowID;
+use objc2_core_graphics::kCGWindowBounds;
+use objc2_core_graphics::kCGWindowLayer;
+use objc2_core_graphics::kCGWindowNumber;
+use objc2_core_graphics::kCGWindowOwnerPID;
+use objc2_core_graphics::CGDirectDisplayID;
+use objc2_core_graphics::CGDisplayRotation;
+use objc2_core_graphics::CGError;
+use objc2_core_graphics::CGEvent;
+use objc2_core_graphics::CGEventFlags;
+use objc2_core_graphics::CGEventTapLocation;
+use objc2_core_graphics::CGEventType;
+use objc2_core_graphics::CGMouseButton;
+use objc2_core_graphics::CGRectGetMidX;
+use objc2_core_graphics::CGRectGetMinY;
+use objc2_core_graphics::CGRectMakeWithDictionaryRepresentation;
+use objc2_core_graphics::CGWindowID;
+use objc2_core_graphics::CGWindowLevelForKey;
+use objc2_core_graphics::CGWindowLevelKey;
+use objc2_core_graphics::CGWindowListCopyWindowInfo;
+use objc2_core_graphics::CGWindowListOption;
+use objc2_foundation::ns_string;
+use objc2_foundation::NSNumber;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+use private::CGSCopyManagedDisplaySpaces;
+use private::CGSGetActiveSpace;
+use private::CGSMainConnectionID;
+use private::CGSSpaceID;
+
+/// Helper function to extract an UI element's origin.
+fn get_ui_element_origin(ui_element: &CFRetained<AXUIElement>) -> Result<CGPoint, Error> {
+    let mut position_value: *const CFType = std::ptr::null();
+    let ptr_to_position_value = NonNull::new(&mut position_value).unwrap();
+    let position_attr = CFString::from_static_str("AXPosition");
+    let error = unsafe { ui_element.copy_attribute_value(&position_attr, ptr_to_position_value) };
+
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+    assert!(!position_value.is_null());
+
+    let position: CFRetained<AXValue> =
+        unsafe { CFRetained::from_raw(NonNull::new(position_value.cast_mut().cast()).unwrap()) };
+
+    let mut position_cg_point = CGPoint::ZERO;
+    let ptr_to_position_cg_point =
+        NonNull::new((&mut position_cg_point as *mut CGPoint).cast()).unwrap();
+
+    let result = unsafe { position.value(AXValueType::CGPoint, ptr_to_position_cg_point) };
+    assert!(result, "type mismatched");
+
+    Ok(position_cg_point)
+}
+
+/// Helper function to extract an UI element's size.
+fn get_ui_element_size(ui_element: &CFRetained<AXUIElement>) -> Result<CGSize, Error> {
+    let mut size_value: *const CFType = std::ptr::null();
+    let ptr_to_size_value = NonNull::new(&mut size_value).unwrap();
+    let size_attr = CFString::from_static_str("AXSize");
+    let error = unsafe { ui_element.copy_attribute_value(&size_attr, ptr_to_size_value) };
+
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+    assert!(!size_value.is_null());
+
+    let size: CFRetained<AXValue> =
+        unsafe { CFRetained::from_raw(NonNull::new(size_value.cast_mut().cast()).unwrap()) };
+
+    let mut size_cg_size = CGSize::ZERO;
+    let ptr_to_size_cg_size = NonNull::new((&mut size_cg_size as *mut CGSize).cast()).unwrap();
+
+    let result = unsafe { size.value(AXValueType::CGSize, ptr_to_size_cg_size) };
+    assert!(result, "type mismatched");
+
+    Ok(size_cg_size)
+}
+
+/// Get the frontmost/focused window (as an UI element).
+fn get_frontmost_window() -> Result<CFRetained<AXUIElement>, Error> {
+    let workspace = unsafe { NSWorkspace::sharedWorkspace() };
+    let frontmost_app =
+        unsafe { workspace.frontmostApplication() }.ok_or(Error::CannotFindFocusWindow)?;
+
+    let pid = unsafe { frontmost_app.processIdentifier() };
+
+    let app_element = unsafe { AXUIElement::new_application(pid) };
+
+    let mut window_element: *const CFType = std::ptr::null();
+    let ptr_to_window_element = NonNull::new(&mut window_element).unwrap();
+    let focused_window_attr = CFString::from_static_str("AXFocusedWindow");
+
+    let error =
+        unsafe { app_element.copy_attribute_value(&focused_window_attr, ptr_to_window_element) };
+
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+    assert!(!window_element.is_null());
+
+    let window_element: *mut AXUIElement = window_element.cast::<AXUIElement>().cast_mut();
+
+    let window = unsafe { CFRetained::from_raw(NonNull::new(window_element).unwrap()) };
+
+    Ok(window)
+}
+
+/// Get the CGWindowID of the frontmost/focused window.
+pub(crate) fn get_frontmost_window_id() -> Result<CGWindowID, Error> {
+    let element = get_frontmost_window()?;
+    let ptr: NonNull<AXUIElement> = CFRetained::as_ptr(&element);
+
+    let mut window_id_buffer: CGWindowID = 0;
+    let error =
+        unsafe { private::_AXUIElementGetWindow(ptr.as_ptr(), &mut window_id_buffer as *mut _) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    Ok(window_id_buffer)
+}
+
+/// Read a number from a window dictionary returned by
+/// `CGWindowListCopyWindowInfo()`, `None` if the key does not exist.
+unsafe fn window_info_number(window: &CFDictionary, key: &CFString) -> Option<i64> {
+    let key: *const CFString = key;
+    let mut ptr_to_value_buffer: *const c_void = std::ptr::null();
+    let key_exists = unsafe {
+        window.value_if_present(key.cast::<c_void>(), &mut ptr_to_value_buffer as *mut _)
+    };
+    if !key_exists || ptr_to_value_buffer.is_null() {
+        return None;
+    }
+
+    unsafe { (*ptr_to_value_buffer.cast::<CFNumber>()).as_i64() }
+}
+
+/// Read the bounds from a window dictionary returned by
+/// `CGWindowListCopyWindowInfo()`, they are in the flipped coordinate system.
+/// `None` if the key does not exist.
+unsafe fn window_info_bounds(window: &CFDictionary) -> Option<Rect> {
+    let key: *const CFString = unsafe { kCGWindowBounds };
+    let mut ptr_to_value_buffer: *const c_void = std::ptr::null();
+    let key_exists = unsafe {
+        window.value_if_present(key.cast::<c_void>(), &mut ptr_to_value_buffer as *mut _)
+    };
+    if !key_exists || ptr_to_value_buffer.is_null() {
+        return None;
+    }
+
+    let mut bounds = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(0.0, 0.0));
+    let is_rect = unsafe {
+        CGRectMakeWithDictionaryRepresentation(
+            Some(&*ptr_to_value_buffer.cast::<CFDictionary>()),
+            &mut bounds,
+        )
+    };
+
+    is_rect.then(|| Rect::from(bounds))
+}
+
+/// List the CGWindowIDs of all the windows, including the ones that are not
+/// on screen.
+pub(crate) fn list_window_ids() -> Result<Vec<CGWindowID>, Error> {
+    unsafe {
+        let Some(windows) = CGWindowListCopyWindowInfo(
+            CGWindowListOption::OptionAll | CGWindowListOption::ExcludeDesktopElements,
+            kCGNullWindowID,
+        ) else {
+            // Never report "no windows" on failure, callers prune their
+            // per-window state by this list.
+            return Err(Error::CGError(CGError::Failure));
+        };
+
+        let ids = (0..windows.count())
+            .filter_map(|i| {
+                let window: &CFDictionary = &*windows.value_at_index(i).cast::<CFDictionary>();
+                window_info_number(window, kCGWindowNumber)
+            })
+            // cast is safe, CGWindowID is u32
+            .map(|id| id as CGWindowID)
+            .collect();
+
+        Ok(ids)
+    }
+}
+
+/// Focus the window with this CGWindowID, i.e., activate its application and
+/// raise it.
+pub(crate) fn focus_window(window_id: CGWindowID) -> Result<(), Error> {
+    // Find the application that owns this window
+    let pid = unsafe {
+        let windows =
+            CGWindowListCopyWindowInfo(CGWindowListOption::OptionIncludingWindow, window_id)
+                .ok_or(Error::NoSuchWindow)?;
+        if windows.count() == 0 {
+            return Err(Error::NoSuchWindow);
+        }
+        let window: &CFDictionary = &*windows.value_at_index(0).cast::<CFDictionary>();
+        window_info_number(window, kCGWindowOwnerPID).ok_or(Error::NoSuchWindow)?
+    };
+    // cast is safe, pid_t is i32
+    let app_element = unsafe { AXUIElement::new_application(pid as i32) };
+
+    let mut windows_ref: *const CFType = std::ptr::null();
+    let windows_attr = CFString::from_static_str("AXWindows");
+    let error = unsafe {
+        app_element.copy_attribute_value(&windows_attr, NonNull::new(&mut windows_ref).unwrap())
+    };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+    assert!(!windows_ref.is_null());
+    let windows: CFRetained<CFArray> = unsafe {
+        CFRetained::from_raw(NonNull::new(windows_ref.cast::<CFArray>().cast_mut()).unwrap())
+    };
+
+    let window = (0..windows.count())
+        .map(|i| {
+            unsafe { windows.value_at_index(i) }
+                .cast::<AXUIElement>()
+                .cast_mut()
+        })
+        .find(|element| {
+            let mut id: CGWindowID = 0;
+            let error = unsafe { private::_AXUIElementGetWindow(*element, &mut id as *mut _) };
+            error == AXError::Success && id == window_id
+        })
+        .ok_or(Error::NoSuchWindow)?;
+    let window: &AXUIElement = unsafe { &*window };
+
+    let true_ref: CFRetained<CFBoolean> = CFBoolean::new(true).retain();
+    let frontmost_attr = CFString::from_static_str("AXFrontmost");
+    let error = unsafe { app_element.set_attribute_value(&frontmost_attr, true_ref.deref()) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    let raise_action = CFString::from_static_str("AXRaise");
+    let error = unsafe { window.perform_action(&raise_action) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    Ok(())
+}
+
+/// Returns the workspace ID list grouped by display.  For example, suppose you
+/// have 2 displays and 10 workspaces (5 workspaces per display), then this
+/// function might return something like:
+///
+/// ```text
+/// [
+///   [8, 11, 12, 13, 24],
+///   [519, 77, 15, 249, 414]
+/// ]
+/// ```
+///
+/// Even though this function return macOS internal space IDs, they should correspond
+/// to the logical workspace that users are familiar with.  The display that contains
+/// workspaces `[8, 11, 12, 13, 24]` should be your main display; workspace 8 represents
+/// Desktop 1, and workspace 414 represents Desktop 10.
+fn workspace_ids_grouped_by_display() -> Vec<Vec<CGSSpaceID>> {
+    unsafe {
+        let mut ret = Vec::new();
+        let conn = CGSMainConnectionID();
+
+        let display_spaces_raw = CGSCopyManagedDisplaySpaces(conn);
+        let display_spaces: CFRetained<CFArray> =
+            CFRetained::from_raw(NonNull::new(display_spaces_raw).unwrap());
+
+        let key_spaces: CFRetained<CFString> = CFString::from_static_str("Spaces");
+        let key_spaces_ptr: NonNull<CFString> = CFRetained::as_ptr(&key_spaces);
+        let key_id64: CFRetained<CFString> = CFString::from_static_str("id64");
+        let key_id64_ptr: NonNull<CFString> = CFRetained::as_ptr(&key_id64);
+
+        for i in 0..display_spaces.count() {
+            let mut workspaces_of_this_display = Vec::new();
+
+            let dict_ref = display_spaces.value_at_index(i);
+            let dict: &CFDictionary = &*(dict_ref as *const CFDictionary);
+
+            let mut ptr_to_value_buffer: *const c_void = std::ptr::null();
+            let key_exists = dict.value_if_present(
+                key_spaces_ptr.as_ptr().cast::<c_void>().cast_const(),
+                &mut ptr_to_value_buffer as *mut _,
+            );
+            assert!(key_exists);
+            assert!(!ptr_to_value_buffer.is_null());
+
+            let spaces_raw: *const CFArray = ptr_to_value_buffer.cast::<CFArray>();
+
+            let spaces = &*spaces_raw;
+
+            for idx in 0..spaces.count() {
+                let workspace_dictionary: &CFDictionary =
+                    &*spaces.value_at_index(idx).cast::<CFDictionary>();
+
+                let mut ptr_to_value_buffer: *const c_void = std::ptr::null();
+                let key_exists = workspace_dictionary.value_if_present(
+                    key_id64_ptr.as_ptr().cast::<c_void>().cast_const(),
+                    &mut ptr_to_value_buffer as *mut _,
+                );
+                assert!(key_exists);
+                assert!(!ptr_to_value_buffer.is_null());
+
+                let ptr_workspace_id = ptr_to_value_buffer.cast::<CFNumber>();
+                let workspace_id = (&*ptr_workspace_id).as_i32().unwrap();
+
+                workspaces_of_this_display.push(workspace_id);
+            }
+
+            ret.push(workspaces_of_this_display);
+        }
+
+        ret
+    }
+}
+
+/// Get the next workspace's logical ID.  By logical ID, we mean the ID that
+/// users are familiar with, workspace 1/2/3 and so on, rather than the internal
+/// `CGSSpaceID`.
+///
+/// NOTE that this function returns None when the current workspace is the last
+/// workspace in the current display.
+pub(crate) fn get_next_workspace_logical_id() -> Option<usize> {
+    let window_server_connection = unsafe { CGSMainConnectionID() };
+    let current_workspace_id = unsafe { CGSGetActiveSpace(window_server_connection) };
+
+    // Logical ID starts from 1
+    let mut logical_id = 1_usize;
+
+    for workspaces_in_a_display in workspace_ids_grouped_by_display() {
+        for (idx, workspace_raw_id) in workspaces_in_a_display.iter().enumerate() {
+            if *workspace_raw_id == current_workspace_id {
+                // We found it, now check if it is the last workspace in this display
+                if idx == workspaces_in_a_display.len() - 1 {
+                    return None;
+                } else {
+                    return Some(logical_id + 1);
+                }
+            } else {
+                logical_id += 1;
+                continue;
+            }
+        }
+    }
+
+    unreachable!("unless the private API CGSGetActiveSpace() is broken, it should return an ID that is in the workspace ID list")
+}
+
+/// Get the previous workspace's logical ID.
+///
+/// See [`get_next_workspace_logical_id`] for the doc.
+pub(crate) fn get_previous_workspace_logical_id() -> Option<usize> {
+    let window_server_connection = unsafe { CGSMainConnectionID() };
+    let current_workspace_id = unsafe { CGSGetActiveSpace(window_server_connection) };
+
+    // Logical ID starts from 1
+    let mut logical_id = 1_usize;
+
+    for workspaces_in_a_display in workspace_ids_grouped_by_display() {
+        for (idx, workspace_raw_id) in workspaces_in_a_display.iter().enumerate() {
+            if *workspace_raw_id == current_workspace_id {
+                // We found it, now check if it is the first workspace in this display
+                if idx == 0 {
+                    return None;
+                } else {
+                    // this sub operation is safe, logical_id is at least 2
+                    return Some(logical_id - 1);
+                }
+            } else {
+                logical_id += 1;
+                continue;
+            }
+        }
+    }
+
+    unreachable!("unless the private API CGSGetActiveSpace() is broken, it should return an ID that is in the workspace ID list")
+}
+
+/// Move the frontmost window to the specified workspace.
+///
+/// Credits to the Silica library
+///
+/// * https://github.com/ianyh/Silica/blob/b91a18dbb822e99ce6b487d1cb4841e863139b2a/Silica/Sources/SIWindow.m#L215-L260
+/// * https://github.com/ianyh/Silica/blob/b91a18dbb822e99ce6b487d1cb4841e863139b2a/Silica/Sources/SISystemWideElement.m#L29-L65
+pub(crate) fn move_frontmost_window_to_workspace(space: usize) -> Result<(), Error> {
+    if space == 0 {
+        return Err(Error::NoSuchWorkspace);
+    }
+    if space > 16 {
+        return Err(Error::TooManyWorkspace);
+    }
+
+    let window_frame = get_frontmost_window_frame()?;
+    let close_button_frame = get_frontmost_window_close_button_frame()?;
+
+    let mouse_cursor_point = CGPoint::new(
+        unsafe { CGRectGetMidX(close_button_frame) },
+        window_frame.origin.y
+            + (window_frame.origin.y - unsafe { CGRectGetMinY(close_button_frame) }).abs() / 2.0,
+    );
+
+    let mouse_move_event = unsafe {
+        CGEvent::new_mouse_event(
+            None,
+            CGEventType::MouseMoved,
+            mouse_cursor_point,
+            CGMouseButton::Left,
+        )
+    };
+    let mouse_drag_event = unsafe {
+        CGEvent::new_mouse_event(
+            None,
+            CGEventType::LeftMouseDragged,
+            mouse_cursor_point,
+            CGMouseButton::Left,
+        )
+    };
+    let mouse_down_event = unsafe {
+        CGEvent::new_mouse_event(
+            None,
+            CGEventType::LeftMouseDown,
+            mouse_cursor_point,
+            CGMouseButton::Left,
+        )
+    };
+    let mouse_up_event = unsafe {
+        CGEvent::new_mouse_event(
+            None,
+            CGEventType::LeftMouseUp,
+            mouse_cursor_point,
+            CGMouseButton::Left,
+        )
+    };
+
+    unsafe {
+        CGEvent::set_flags(mouse_move_event.as_deref(), CGEventFlags(0));
+        CGEvent::set_flags(mouse_down_event.as_deref(), CGEventFlags(0));
+        CGEvent::set_flags(mouse_up_event.as_deref(), CGEventFlags(0));
+
+        // Move the mouse into place at the window's toolbar
+        CGEvent::post(CGEventTapLocation::HIDEventTap, mouse_move_event.as_deref());
+        // Mouse down to set up the drag
+        CGEvent::post(CGEventTapLocation::HIDEventTap, mouse_down_event.as_deref());
+        // Drag event to grab hold of the window
+        CGEvent::post(CGEventTapLocation::HIDEventTap, mouse_drag_event.as_deref());
+    }
+
+    // cast is safe as space is in range [1, 16]
+    let hot_key: c_ushort = 118 + space as c_ushort - 1;
+
+    let mut flags: c_uint = 0;
+    let mut key_code: c_ushort = 0;
+    let error = unsafe {
+        private::CGSGetSymbolicHotKeyValue(hot_key, std::ptr::null_mut(), &mut key_code, &mut flags)
+    };
+    if error != CGError::Success {
+        return Err(Error::CGError(error));
+    }
+
+    unsafe {
+        // If the hotkey is disabled, enable it.
+        if !private::CGSIsSymbolicHotKeyEnabled(hot_key) {
+            if private::CGSSetSymbolicHotKeyEnabled(hot_key, true) != CGError::Success {
+                return Err(Error::CGError(error));
+            }
+        }
+    }
+
+    let opt_keyboard_event = unsafe { CGEvent::new_keyboard_event(None, key_code, true) };
+    unsafe {
+        // cast is safe (uint -> u64)
+        CGEvent::set_flags(opt_keyboard_event.as_deref(), CGEventFlags(flags as u64));
+    }
+
+    let keyboard_event = opt_keyboard_event.unwrap();
+    let event = unsafe { NSEvent::eventWithCGEvent(&keyboard_event) }.unwrap();
+
+    let keyboard_event_up = unsafe { CGEvent::new_keyboard_event(None, event.keyCode(), false) };
+    unsafe {
+        CGEvent::set_flags(keyboard_event_up.as_deref(), CGEventFlags(0));
+
+        // Send the shortcut command to get Mission Control to switch spaces from under the window.
+        CGEvent::post(CGEventTapLocation::HIDEventTap, event.CGEvent().as_deref());
+        CGEvent::post(
+            CGEventTapLocation::HIDEventTap,
+            keyboard_event_up.as_deref(),
+        );
+    }
+
+    unsafe {
+        // Let go of the window.
+        CGEvent::post(CGEventTapLocation::HIDEventTap, mouse_up_event.as_deref());
+    }
+
+    Ok(())
+}
+
+pub(crate) fn get_frontmost_window_origin() -> Result<Point, Error> {
+    let frontmost_window = get_frontmost_window()?;
+    get_ui_element_origin(&frontmost_window).map(Point::from)
+}
+
+pub(crate) fn get_frontmost_window_size() -> Result<Size, Error> {
+    let frontmost_window = get_frontmost_window()?;
+    get_ui_element_size(&frontmost_window).map(Size::from)
+}
+
+fn get_frontmost_window_frame() -> Result<Rect, Error> {
+    let origin = get_frontmost_window_origin()?;
+    let size = get_frontmost_window_size()?;
+
+    Ok(Rect { origin, size })
+}
+
+/// Get the frontmost window's close button, then extract its frame.
+fn get_frontmost_window_close_button_frame() -> Result<CGRect, Error> {
+    let window = get_frontmost_window()?;
+
+    let mut ptr_to_close_button: *const CFType = std::ptr::null();
+    let ptr_to_buffer = NonNull::new(&mut ptr_to_close_button).unwrap();
+
+    let close_button_attribute = CFString::from_static_str("AXCloseButton");
+    let error = unsafe { window.copy_attribute_value(&close_button_attribute, ptr_to_buffer) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+    assert!(!ptr_to_close_button.is_null());
+
+    let close_button_element = ptr_to_close_button.cast::<AXUIElement>().cast_mut();
+    let close_button = unsafe { CFRetained::from_raw(NonNull::new(close_button_element).unwrap()) };
+
+    let origin = get_ui_element_origin(&close_button)?;
+    let size = get_ui_element_size(&close_button)?;
+
+    Ok(CGRect { origin, size })
+}
+
+/// This function returns all the displays, the main display, i.e., the one
+/// with the menu bar in the display arrangement settings, comes first.
+///
+/// Their reserved space is not taken from the [`visibleFrame()`][vf_doc] API,
+/// which has 2 bugs:
+///
+/// 1. It assumes the Dock is on the main display, which in reality depends on
+///    how users arrange their displays and the "Dock position on screen" setting
+///    entry.
+/// 2. For non-main displays, it assumes that they don't have a menu bar, but macOS
+///    puts a menu bar on every display when "Displays have separate Spaces" is
+///    on.
+///
+/// Instead, the menu bar is reserved on every display that has one, and the
+/// Dock on the display it is actually on, see [`get_dock_frame()`].
+///
+/// [vf_doc]: https://developer.apple.com/documentation/AppKit/NSScreen/visibleFrame
+pub(crate) fn list_displays() -> Result<Vec<Display>, Error> {
+    let main_thread_marker = MainThreadMarker::new().ok_or(Error::NotInMainThread)?;
+    let screens = NSScreen::screens(main_thread_marker).to_vec();
+
+    let Some(main_screen) = screens.first() else {
+        return Ok(Vec::new());
+    };
+    let main_screen_height = main_screen.frame().size.height;
+
+    // visibleFrame() does get the menu bar of the main display right, it is 0
+    // if the menu bar hides automatically.
+    let main_menu_bar_height = Insets::between(
+        &Rect::from(main_screen.frame()),
+        &Rect::from(main_screen.visibleFrame()).flip_y(main_screen_height),
+    )
+    .top;
+    let has_separate_menu_bars = unsafe { NSScreen::screensHaveSeparateSpaces(main_thread_marker) };
+    let menu_bar_height = unsafe { NSStatusBar::systemStatusBar().thickness() };
+    let dock_frame = get_dock_frame();
+
+    let displays = screens
+        .iter()
+        .filter_map(|ns_screen| {
+            let display_id = get_screen_display_id(ns_screen)?;
+            let is_main_screen = ns_screen == main_screen;
+
+            // NSScreen is an AppKit API, which uses unflipped coordinate
+            // system, flip it
+            let frame = Rect::from(ns_screen.frame()).flip_y(main_screen_height);
+            let mut reserved = dock_frame
+                .map(|dock_frame| reserved_by_dock(&frame, &dock_frame))
+                .unwrap_or(Insets::ZERO);
+            reserved.top = if is_main_screen {
+                main_menu_bar_height
+            } else if has_separate_menu_bars && main_menu_bar_height > 0.0 {
+                menu_bar_height
+            } else {
+                0.0
+            };
+
+            Some(Display {
+                id: DisplayId(display_id.into()),
+                name: unsafe { ns_screen.localizedName() }.to_string(),
+                frame,
+                reserved,
+                scale_factor: ns_screen.backingScaleFactor(),
+                // cast is safe, it is one of 0, 90, 180 and 270
+                rotation: unsafe { CGDisplayRotation(display_id) } as u32,
+                is_primary: is_main_screen,
+            })
+        })
+        .collect();
+
+    Ok(displays)
+}
+
+/// Return the frame of the Dock, i.e., the Dock window in the Dock window
+/// level, `None` if it is not on screen.
+fn get_dock_frame() -> Option<Rect> {
+    let dock_pid = unsafe {
+        NSRunningApplication::runningApplicationsWithBundleIdentifier(ns_string!("com.apple.dock"))
+            .firstObject()?
+            .processIdentifier()
+    };
+    let dock_level = unsafe { CGWindowLevelForKey(CGWindowLevelKey::DockWindowLevelKey) };
+
+    unsafe {
+        let windows =
+            CGWindowListCopyWindowInfo(CGWindowListOption::OptionOnScreenOnly, kCGNullWindowID)?;
+
+        (0..windows.count()).find_map(|i| {
+            let window: &CFDictionary = &*windows.value_at_index(i).cast::<CFDictionary>();
+            let is_dock = window_info_number(window, kCGWindowOwnerPID) == Some(dock_pid.into())
+                && window_info_number(window, kCGWindowLayer) == Some(dock_level.into());
+
+            if is_dock {
+                window_info_bounds(window)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// The space the Dock at `dock_frame` takes on the display at `frame`.
+fn reserved_by_dock(frame: &Rect, dock_frame: &Rect) -> Insets {
+    let Some(dock) = dock_frame.intersection(frame) else {
+        return Insets::ZERO;
+    };
+    // Not the Dock itself, but some helper window of it
+    if dock.area() >= frame.area() / 2.0 {
+        return Insets::ZERO;
+    }
+
+    if dock.size.width >= dock.size.height {
+        Insets {
+            bottom: frame.max().y - dock.min().y,
+            ..Insets::ZERO
+        }
+    } else if dock.center().x < frame.center().x {
+        Insets {
+            left: dock.max().x - frame.min().x,
+            ..Insets::ZERO
+        }
+    } else {
+        Insets {
+            right: frame.max().x - dock.min().x,
+            ..Insets::ZERO
+        }
+    }
+}
+
+/// Return the `CGDirectDisplayID` of this screen, `None` if AppKit does not
+/// tell.
+fn get_screen_display_id(ns_screen: &NSScreen) -> Option<CGDirectDisplayID> {
+    let number = ns_screen
+        .deviceDescription()
+        .objectForKey(ns_string!("NSScreenNumber"))?
+        .downcast::<NSNumber>()
+        .ok()?;
+
+    Some(number.unsignedIntValue())
+}
+
+/// Move the frontmost window's origin to `origin`.
+pub(crate) fn move_frontmost_window(origin: Point) -> Result<(), Error> {
+    let frontmost_window = get_frontmost_window()?;
+
+    let mut point = CGPoint::from(origin);
+    let ptr_to_point = NonNull::new((&mut point as *mut CGPoint).cast::<c_void>()).unwrap();
+    let pos_value = unsafe { AXValue::new(AXValueType::CGPoint, ptr_to_point) }
+        .ok_or(Error::AXError(AXError::Failure))?;
+    let pos_attr = CFString::from_static_str("AXPosition");
+
+    let error = unsafe { frontmost_window.set_attribute_value(&pos_attr, pos_value.deref()) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    Ok(())
+}
+
+/// Set the frontmost window's frame to the specified frame - adjust size and
+/// location at the same time.
+pub(crate) fn set_frontmost_window_frame(frame: Rect) -> Result<(), Error> {
+    let frontmost_window = get_frontmost_window()?;
+
+    let mut point = CGPoint::from(frame.origin);
+    let ptr_to_point = NonNull::new((&mut point as *mut CGPoint).cast::<c_void>()).unwrap();
+    let pos_value = unsafe { AXValue::new(AXValueType::CGPoint, ptr_to_point) }
+        .ok_or(Error::AXError(AXError::Failure))?;
+    let pos_attr = CFString::from_static_str("AXPosition");
+
+    let error = unsafe { frontmost_window.set_attribute_value(&pos_attr, pos_value.deref()) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    let mut size = CGSize::from(frame.size);
+    let ptr_to_size = NonNull::new((&mut size as *mut CGSize).cast::<c_void>()).unwrap();
+    let size_value = unsafe { AXValue::new(AXValueType::CGSize, ptr_to_size) }
+        .ok_or(Error::AXError(AXError::Failure))?;
+    let size_attr = CFString::from_static_str("AXSize");
+
+    let error = unsafe { frontmost_window.set_attribute_value(&size_attr, size_value.deref()) };
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    Ok(())
+}
+
+/// Toggle the fullscreen mode of the frontmost window.
+pub(crate) fn toggle_fullscreen() -> Result<(), Error> {
+    let frontmost_window = get_frontmost_window()?;
+    let fullscreen_attr = CFString::from_static_str("AXFullScreen");
+
+    let mut current_value_ref: *const CFType = std::ptr::null();
+    let error = unsafe {
+        frontmost_window.copy_attribute_value(
+            &fullscreen_attr,
+            NonNull::new(&mut current_value_ref).unwrap(),
+        )
+    };
+
+    // TODO: If the attribute doesn't exist, error won't be Success as well.
+    // Before we handle that, we need to know the error case that will be
+    // returned in that case.
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+    assert!(!current_value_ref.is_null());
+
+    let current_value = unsafe {
+        let retained_boolean: CFRetained<CFBoolean> = CFRetained::from_raw(
+            NonNull::new(current_value_ref.cast::<CFBoolean>().cast_mut()).unwrap(),
+        );
+        retained_boolean.as_bool()
+    };
+
+    let new_value = !current_value;
+    let new_value_ref: CFRetained<CFBoolean> = CFBoolean::new(new_value).retain();
+
+    let error =
+        unsafe { frontmost_window.set_attribute_value(&fullscreen_attr, new_value_ref.deref()) };
+
+    if error != AXError::Success {
+        return Err(Error::AXError(error));
+    }
+
+    Ok(())
+}
+
+/// The macOS backend, built on top of the Accessibility APIs.
+///
+/// NOTE: its functions should be called in the main thread, or they will error out.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MacOsBackend;
+
+impl WindowBackend for MacOsBackend {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        get_frontmost_window_id().map(|id| WindowId(id.into()))
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        Ok(list_window_ids()?
+            .into_iter()
+            .map(|id| WindowId(id.into()))
+            .collect())
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        let window = CGWindowID::try_from(window.0).map_err(|_| Error::NoSuchWindow)?;
+        focus_window(window)
+    }
+
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        get_frontmost_window_frame()
+    }
+
+    /// The accessibility API does not expose it.
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        Ok(None)
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        set_frontmost_window_frame(frame)
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        move_frontmost_window(origin)
+    }
+
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        list_displays()
+    }
+
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        Ok(get_next_workspace_logical_id())
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        Ok(get_previous_workspace_logical_id())
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        move_frontmost_window_to_workspace(space)
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        toggle_fullscreen()
+    }
+}
@@ -0,0 +1,693 @@
+//! niri backend.
+//!
+//! It talks to niri over its [IPC socket][ipc] (`$NIRI_SOCKET`).
+//!
+//! niri lays tiled windows out on an infinite scrolling strip, their frames
+//! are decided by the layout and are not reported over IPC.  This backend
+//! does not make them floating behind the user's back, getting the frame of
+//! a tiled window, or moving or resizing it, fails with
+//! [`Error::Unsupported`], so does moving a floating window to another
+//! display.  Frames that only stick out of the display are clamped to its
+//! edges.  Workspace and fullscreen actions work on every window, since
+//! [`apply_to_focused_window()`] does not need the frame for them.
+//!
+//! NOTE: niri does not report the space reserved by bars over IPC, so no
+//! space is reserved on any display.
+//!
+//! [ipc]: https://github.com/YaLTeR/niri/wiki/IPC
+//! [`apply_to_focused_window()`]: crate::apply_to_focused_window
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+/// Every reply is either `{"Ok": ...}` or `{"Err": "..."}`.
+#[derive(Debug, Deserialize)]
+enum Reply<T> {
+    Ok(T),
+    Err(String),
+}
+
+#[derive(Debug, Deserialize)]
+enum FocusedWindowResponse {
+    FocusedWindow(Option<Window>),
+}
+
+#[derive(Debug, Deserialize)]
+enum WindowsResponse {
+    Windows(Vec<Window>),
+}
+
+#[derive(Debug, Deserialize)]
+enum OutputsResponse {
+    Outputs(HashMap<String, Output>),
+}
+
+#[derive(Debug, Deserialize)]
+enum WorkspacesResponse {
+    Workspaces(Vec<Workspace>),
+}
+
+#[derive(Debug, Deserialize)]
+struct Window {
+    id: u64,
+    workspace_id: Option<u64>,
+    is_floating: bool,
+    /// Only reported by niri 25.05 or newer.
+    #[serde(default)]
+    layout: Option<WindowLayout>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WindowLayout {
+    /// Size of the tile, i.e., the window with its borders.
+    tile_size: [f64; 2],
+    window_size: [i32; 2],
+    /// Relative to the output, only present for floating windows.
+    tile_pos_in_workspace_view: Option<[f64; 2]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    name: String,
+    /// `None` if the output is disabled.
+    logical: Option<LogicalOutput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LogicalOutput {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale: f64,
+    /// e.g., `Normal`, `90` or `Flipped90`, counter-clockwise.
+    transform: String,
+}
+
+impl From<&LogicalOutput> for Rect {
+    fn from(logical: &LogicalOutput) -> Self {
+        Rect::new(
+            Point::new(logical.x as f64, logical.y as f64),
+            Size::new(logical.width as f64, logical.height as f64),
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Workspace {
+    id: u64,
+    /// Index on its output, starts from 1.
+    idx: u8,
+    output: Option<String>,
+}
+
+/// An enabled output.
+#[derive(Debug)]
+struct Screen {
+    name: String,
+    frame: Rect,
+    scale: f64,
+    /// Clockwise, in degrees.
+    rotation: u32,
+}
+
+/// The niri backend.
+#[derive(Debug, Clone)]
+pub struct NiriBackend {
+    socket_path: PathBuf,
+}
+
+impl NiriBackend {
+    /// Use the socket specified by `$NIRI_SOCKET`.
+    pub fn connect() -> Result<Self, Error> {
+        let path = std::env::var_os("NIRI_SOCKET")
+            .ok_or_else(|| Error::IpcError("$NIRI_SOCKET is not set".into()))?;
+
+        Self::connect_to(path)
+    }
+
+    /// Use the socket at `path`.
+    ///
+    /// A new connection is made for every request, like what niri's own
+    /// client does.
+    pub fn connect_to(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let socket_path = path.as_ref().to_path_buf();
+        // Fail early if the socket does not exist
+        std::fs::metadata(&socket_path)?;
+
+        Ok(Self { socket_path })
+    }
+
+    /// Send a request and deserialize the reply.
+    fn request<T: DeserializeOwned>(&self, request: Value) -> Result<T, Error> {
+        let mut stream = UnixStream::connect(&self.socket_path)?;
+        stream.write_all(format!("{}\n", request).as_bytes())?;
+        stream.shutdown(Shutdown::Write)?;
+
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        let reply: Reply<T> =
+            serde_json::from_str(&reply).map_err(|error| Error::IpcError(error.to_string()))?;
+
+        match reply {
+            Reply::Ok(response) => Ok(response),
+            Reply::Err(error) => Err(Error::IpcError(error)),
+        }
+    }
+
+    fn action(&self, action: Value) -> Result<(), Error> {
+        self.request::<IgnoredAny>(json!({ "Action": action }))
+            .map(|_| ())
+    }
+
+    fn focused_window(&self) -> Result<Window, Error> {
+        let FocusedWindowResponse::FocusedWindow(window) = self.request(json!("FocusedWindow"))?;
+        window.ok_or(Error::CannotFindFocusWindow)
+    }
+
+    fn workspaces(&self) -> Result<Vec<Workspace>, Error> {
+        let WorkspacesResponse::Workspaces(workspaces) = self.request(json!("Workspaces"))?;
+        Ok(workspaces)
+    }
+
+    /// The workspace the focused window is on.
+    fn focused_workspace(&self) -> Result<(Workspace, Vec<Workspace>), Error> {
+        let workspace_id = self
+            .focused_window()?
+            .workspace_id
+            .ok_or(Error::CannotFindFocusWindow)?;
+        let mut workspaces = self.workspaces()?;
+        let index = workspaces
+            .iter()
+            .position(|workspace| workspace.id == workspace_id)
+            .ok_or_else(|| Error::IpcError("cannot find the window's workspace".into()))?;
+        let workspace = workspaces.remove(index);
+
+        Ok((workspace, workspaces))
+    }
+
+    /// List the enabled outputs, sorted by name so that the order is stable.
+    fn list_screens(&self) -> Result<Vec<Screen>, Error> {
+        let OutputsResponse::Outputs(outputs) = self.request(json!("Outputs"))?;
+        let mut screens: Vec<Screen> = outputs
+            .into_values()
+            .filter_map(|output| {
+                let logical = output.logical?;
+                let rotation = if logical.transform.ends_with("90") {
+                    270
+                } else if logical.transform.ends_with("180") {
+                    180
+                } else if logical.transform.ends_with("270") {
+                    90
+                } else {
+                    0
+                };
+                Some(Screen {
+                    name: output.name,
+                    frame: Rect::from(&logical),
+                    scale: logical.scale,
+                    rotation,
+                })
+            })
+            .collect();
+        screens.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(screens)
+    }
+
+    /// The screen that shows `window`.
+    fn screen_of<'a>(&self, window: &Window, screens: &'a [Screen]) -> Result<&'a Screen, Error> {
+        let output = self
+            .workspaces()?
+            .into_iter()
+            .find(|workspace| Some(workspace.id) == window.workspace_id)
+            .and_then(|workspace| workspace.output);
+
+        screens
+            .iter()
+            .find(|screen| Some(&screen.name) == output.as_ref())
+            .or_else(|| screens.first())
+            .ok_or(Error::NoDisplay)
+    }
+
+    /// Return the focused window and its layout, fail if it is not floating.
+    fn focused_floating_window(&self) -> Result<(Window, WindowLayout), Error> {
+        let mut window = self.focused_window()?;
+        if !window.is_floating {
+            return Err(Error::Unsupported(
+                "niri cannot get, move or resize the frames of tiled windows".into(),
+            ));
+        }
+        let layout = window
+            .layout
+            .take()
+            .ok_or_else(|| Error::IpcError("niri 25.05 or newer is required".into()))?;
+
+        Ok((window, layout))
+    }
+
+    /// Move the tile of a floating window, which is `size` large, to
+    /// `origin`, clamped so that it stays on its screen if it fits.
+    ///
+    /// Fail with [`Error::Unsupported`] if the center of the tile would be
+    /// on another screen, niri does not move windows between outputs by
+    /// their positions.
+    fn move_floating_window(
+        &self,
+        window: &Window,
+        screens: &[Screen],
+        origin: Point,
+        size: Size,
+    ) -> Result<(), Error> {
+        let frame = self.screen_of(window, screens)?.frame;
+        if !frame.contains(Rect::new(origin, size).center()) {
+            return Err(Error::Unsupported(
+                "niri cannot move windows to another display by their frames".into(),
+            ));
+        }
+        let min = frame.min();
+        let max = frame.max();
+        let x = origin.x.min(max.x - size.width).max(min.x);
+        let y = origin.y.min(max.y - size.height).max(min.y);
+
+        // Positions of floating windows are relative to their output
+        self.action(json!({
+            "MoveFloatingWindow": {
+                "id": window.id,
+                "x": { "SetFixed": x - min.x },
+                "y": { "SetFixed": y - min.y },
+            }
+        }))
+    }
+}
+
+impl WindowBackend for NiriBackend {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        Ok(WindowId(self.focused_window()?.id))
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        let WindowsResponse::Windows(windows) = self.request(json!("Windows"))?;
+        Ok(windows.iter().map(|window| WindowId(window.id)).collect())
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        if !self.list_window_ids()?.contains(&window) {
+            return Err(Error::NoSuchWindow);
+        }
+
+        self.action(json!({ "FocusWindow": { "id": window.0 } }))
+    }
+
+    /// The frame of a window is the frame of its tile, which includes the
+    /// borders.  Only floating windows have one.
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        let (window, layout) = self.focused_floating_window()?;
+        let size = Size::new(layout.tile_size[0], layout.tile_size[1]);
+        let [x, y] = layout.tile_pos_in_workspace_view.ok_or_else(|| {
+            Error::IpcError("niri did not report the position of the window".into())
+        })?;
+
+        let screens = self.list_screens()?;
+        let screen_origin = self.screen_of(&window, &screens)?.frame.origin;
+
+        Ok(Rect::new(
+            Point::new(screen_origin.x + x, screen_origin.y + y),
+            size,
+        ))
+    }
+
+    /// niri does not expose it.
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        Ok(None)
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        let (window, layout) = self.focused_floating_window()?;
+        let screens = self.list_screens()?;
+        // niri sets the size of the window, without borders
+        let border_width = layout.tile_size[0] - layout.window_size[0] as f64;
+        let border_height = layout.tile_size[1] - layout.window_size[1] as f64;
+
+        self.move_floating_window(&window, &screens, frame.origin, frame.size)?;
+        self.action(json!({
+            "SetWindowWidth": {
+                "id": window.id,
+                "change": { "SetFixed": (frame.size.width - border_width).round() as i32 },
+            }
+        }))?;
+        self.action(json!({
+            "SetWindowHeight": {
+                "id": window.id,
+                "change": { "SetFixed": (frame.size.height - border_height).round() as i32 },
+            }
+        }))
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        let (window, layout) = self.focused_floating_window()?;
+        let screens = self.list_screens()?;
+        let size = Size::new(layout.tile_size[0], layout.tile_size[1]);
+
+        self.move_floating_window(&window, &screens, origin, size)
+    }
+
+    /// niri has no primary output, the first one by name is considered to be.
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        Ok(self
+            .list_screens()?
+            .into_iter()
+            .enumerate()
+            .map(|(index, screen)| Display {
+                id: DisplayId::from_name(&screen.name),
+                name: screen.name,
+                frame: screen.frame,
+                reserved: Insets::ZERO,
+                scale_factor: screen.scale,
+                rotation: screen.rotation,
+                is_primary: index == 0,
+            })
+            .collect())
+    }
+
+    fn get_active_display_id(&self) -> Result<DisplayId, Error> {
+        let window = self.focused_window()?;
+        let screens = self.list_screens()?;
+
+        Ok(DisplayId::from_name(
+            &self.screen_of(&window, &screens)?.name,
+        ))
+    }
+
+    /// niri always keeps an empty workspace at the end of every output, so
+    /// `None` only if the window is on that one.
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let (workspace, others) = self.focused_workspace()?;
+        let next = workspace.idx as usize + 1;
+        let exists = others
+            .iter()
+            .any(|other| other.output == workspace.output && other.idx as usize == next);
+
+        Ok(exists.then_some(next))
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let (workspace, _) = self.focused_workspace()?;
+        Ok((workspace.idx > 1).then(|| workspace.idx as usize - 1))
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        if space == 0 {
+            return Err(Error::NoSuchWorkspace);
+        }
+        let index = u8::try_from(space).map_err(|_| Error::NoSuchWorkspace)?;
+
+        let window = self.focused_window()?;
+        // Follow the window, like what macOS does
+        self.action(json!({
+            "MoveWindowToWorkspace": {
+                "window_id": window.id,
+                "reference": { "Index": index },
+                "focus": true,
+            }
+        }))
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        let window = self.focused_window()?;
+        self.action(json!({ "FullscreenWindow": { "id": window.id } }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::unix::net::UnixListener;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::actions::Action;
+    use crate::actions::ActionOptions;
+    use crate::apply_to_focused_window;
+    use crate::backend::test_dir;
+
+    /// Serve every connection to a socket in `dir` like niri: read one
+    /// request line, send `reply(request)` on one line and hang up.
+    /// Requests are appended to the returned log.
+    fn serve(
+        dir: &Path,
+        reply: impl Fn(&Value) -> Value + Send + 'static,
+    ) -> (PathBuf, Arc<Mutex<Vec<Value>>>) {
+        let path = dir.join("niri.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = String::new();
+                BufReader::new(&stream).read_line(&mut request).unwrap();
+                let request: Value = serde_json::from_str(&request).unwrap();
+                writeln!(stream, "{}", reply(&request)).unwrap();
+                log.lock().unwrap().push(request);
+            }
+        });
+
+        (path, requests)
+    }
+
+    /// Window 7 is focused, floating if `floating`, on the first workspace
+    /// of `DP-1`.  `eDP-1` is rotated by 90 degrees counter-clockwise and
+    /// `HDMI-A-1` is disabled.
+    fn niri(floating: bool) -> impl Fn(&Value) -> Value + Send + 'static {
+        move |request| {
+            let position = floating.then_some([100.0, 50.0]);
+            let window = json!({
+                "id": 7, "workspace_id": 1, "is_floating": floating,
+                "layout": {
+                    "tile_size": [410.0, 310.0], "window_size": [400, 300],
+                    "tile_pos_in_workspace_view": position,
+                },
+            });
+            if request.get("Action").is_some() {
+                return json!({ "Ok": "Handled" });
+            }
+            match request.as_str() {
+                Some("FocusedWindow") => json!({ "Ok": { "FocusedWindow": window } }),
+                Some("Windows") => json!({ "Ok": { "Windows": [
+                    window,
+                    { "id": 8, "workspace_id": 3, "is_floating": false },
+                ] } }),
+                Some("Outputs") => json!({ "Ok": { "Outputs": {
+                    "eDP-1": { "name": "eDP-1", "logical": {
+                        "x": 1920, "y": 0, "width": 1280, "height": 800,
+                        "scale": 1.5, "transform": "90",
+                    } },
+                    "HDMI-A-1": { "name": "HDMI-A-1", "logical": null },
+                    "DP-1": { "name": "DP-1", "logical": {
+                        "x": 0, "y": 0, "width": 1920, "height": 1080,
+                        "scale": 1.0, "transform": "Normal",
+                    } },
+                } } }),
+                Some("Workspaces") => json!({ "Ok": { "Workspaces": [
+                    { "id": 1, "idx": 1, "output": "DP-1" },
+                    { "id": 2, "idx": 2, "output": "DP-1" },
+                    { "id": 3, "idx": 1, "output": "eDP-1" },
+                ] } }),
+                _ => panic!("unexpected request {}", request),
+            }
+        }
+    }
+
+    /// The actions in `requests`.
+    fn actions(requests: &Mutex<Vec<Value>>) -> Vec<Value> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|request| request.get("Action").cloned())
+            .collect()
+    }
+
+    #[test]
+    fn floating_window() {
+        let dir = test_dir("niri-floating-window");
+        let (path, requests) = serve(&dir, niri(true));
+        let mut backend = NiriBackend::connect_to(&path).unwrap();
+
+        assert_eq!(
+            backend.get_frontmost_window_frame().unwrap(),
+            Rect::new(Point::new(100.0, 50.0), Size::new(410.0, 310.0))
+        );
+        backend
+            .set_frontmost_window_frame(Rect::new(Point::new(10.0, 20.0), Size::new(800.0, 600.4)))
+            .unwrap();
+        // Sticks out of DP-1, clamped to it
+        backend
+            .move_frontmost_window(Point::new(1600.0, -20.0))
+            .unwrap();
+        backend.move_frontmost_window_to_workspace(2).unwrap();
+        backend.toggle_fullscreen().unwrap();
+        backend.focus_window(WindowId(8)).unwrap();
+        assert!(matches!(
+            backend.focus_window(WindowId(9)),
+            Err(Error::NoSuchWindow)
+        ));
+
+        assert_eq!(
+            actions(&requests),
+            [
+                json!({ "MoveFloatingWindow": {
+                    "id": 7, "x": { "SetFixed": 10.0 }, "y": { "SetFixed": 20.0 },
+                } }),
+                json!({ "SetWindowWidth": { "id": 7, "change": { "SetFixed": 790 } } }),
+                json!({ "SetWindowHeight": { "id": 7, "change": { "SetFixed": 590 } } }),
+                json!({ "MoveFloatingWindow": {
+                    "id": 7, "x": { "SetFixed": 1510.0 }, "y": { "SetFixed": 0.0 },
+                } }),
+                json!({ "MoveWindowToWorkspace": {
+                    "window_id": 7, "reference": { "Index": 2 }, "focus": true,
+                } }),
+                json!({ "FullscreenWindow": { "id": 7 } }),
+                json!({ "FocusWindow": { "id": 8 } }),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tiled_window() {
+        let dir = test_dir("niri-tiled-window");
+        let (path, requests) = serve(&dir, niri(false));
+        let mut backend = NiriBackend::connect_to(&path).unwrap();
+        let options = ActionOptions::default();
+
+        assert!(matches!(
+            backend.get_frontmost_window_frame(),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            apply_to_focused_window(&mut backend, Action::LeftHalf, &options),
+            Err(Error::Unsupported(_))
+        ));
+
+        // These need no frame
+        apply_to_focused_window(&mut backend, Action::NextDesktop, &options).unwrap();
+        // Already on the first workspace
+        apply_to_focused_window(&mut backend, Action::PreviousDesktop, &options).unwrap();
+        apply_to_focused_window(&mut backend, Action::ToggleFullscreen, &options).unwrap();
+        // Never touched, nothing to restore
+        apply_to_focused_window(&mut backend, Action::Restore, &options).unwrap();
+
+        assert_eq!(
+            actions(&requests),
+            [
+                json!({ "MoveWindowToWorkspace": {
+                    "window_id": 7, "reference": { "Index": 2 }, "focus": true,
+                } }),
+                json!({ "FullscreenWindow": { "id": 7 } }),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn move_to_another_display() {
+        let dir = test_dir("niri-move-to-another-display");
+        let (path, requests) = serve(&dir, niri(true));
+        let mut backend = NiriBackend::connect_to(&path).unwrap();
+
+        assert!(matches!(
+            apply_to_focused_window(&mut backend, Action::NextDisplay, &ActionOptions::default()),
+            Err(Error::Unsupported(_))
+        ));
+        // Mostly on eDP-1
+        assert!(matches!(
+            backend.move_frontmost_window(Point::new(1800.0, 0.0)),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(actions(&requests).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_action() {
+        let dir = test_dir("niri-failed-action");
+        let (path, _) = serve(&dir, |request| match request {
+            Value::Object(_) => json!({ "Err": "no such workspace" }),
+            _ => niri(true)(request),
+        });
+        let mut backend = NiriBackend::connect_to(&path).unwrap();
+
+        assert!(matches!(
+            backend.move_frontmost_window_to_workspace(200),
+            Err(Error::IpcError(error)) if error == "no such workspace"
+        ));
+        assert!(matches!(
+            backend.move_frontmost_window_to_workspace(0),
+            Err(Error::NoSuchWorkspace)
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn displays() {
+        let dir = test_dir("niri-displays");
+        let (path, _) = serve(&dir, niri(true));
+        let backend = NiriBackend::connect_to(&path).unwrap();
+
+        let displays = backend.list_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+
+        assert_eq!(displays[0].name, "DP-1");
+        assert!(displays[0].is_primary);
+        assert_eq!(displays[0].rotation, 0);
+
+        // Logical size and position, rotated clockwise
+        assert_eq!(displays[1].name, "eDP-1");
+        assert!(!displays[1].is_primary);
+        assert_eq!(
+            displays[1].frame,
+            Rect::new(Point::new(1920.0, 0.0), Size::new(1280.0, 800.0))
+        );
+        assert_eq!(displays[1].scale_factor, 1.5);
+        assert_eq!(displays[1].rotation, 270);
+        assert_eq!(displays[1].reserved, Insets::ZERO);
+
+        assert_eq!(
+            backend.get_active_display_id().unwrap(),
+            DisplayId::from_name("DP-1")
+        );
+        assert_eq!(backend.get_next_workspace_logical_id().unwrap(), Some(2));
+        assert_eq!(backend.get_previous_workspace_logical_id().unwrap(), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
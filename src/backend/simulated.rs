@@ -0,0 +1,328 @@
+//! An in-memory desktop, for testing.
+//!
+//! [`SimulatedDesktop`] models screens, windows, focus and workspaces without
+//! talking to any window system, so that actions can be run on every platform
+//! and their results can be asserted exactly.
+
+use std::collections::BTreeMap;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+/// A simulated screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedScreen {
+    /// The whole area of this screen.
+    pub frame: Rect,
+    /// The part of `frame` that windows can use, i.e., excluding menu bars,
+    /// docks and panels.
+    pub visible_frame: Rect,
+}
+
+impl SimulatedScreen {
+    /// A screen without anything reserved, its visible frame is its frame.
+    pub fn new(frame: Rect) -> Self {
+        Self {
+            frame,
+            visible_frame: frame,
+        }
+    }
+
+    pub fn with_visible_frame(frame: Rect, visible_frame: Rect) -> Self {
+        Self {
+            frame,
+            visible_frame,
+        }
+    }
+}
+
+/// A simulated window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedWindow {
+    pub frame: Rect,
+    /// The window cannot be smaller than this.
+    pub min_size: Size,
+    /// The window cannot be larger than this, `None` means unlimited.
+    pub max_size: Option<Size>,
+    /// Logical ID of the workspace this window is on, starts from 1.
+    pub workspace: usize,
+    /// The frame before entering fullscreen, `Some` if the window is in
+    /// fullscreen mode.
+    pub frame_before_fullscreen: Option<Rect>,
+}
+
+impl SimulatedWindow {
+    /// A window on workspace 1 without size constraints.
+    pub fn new(frame: Rect) -> Self {
+        Self {
+            frame,
+            min_size: Size::ZERO,
+            max_size: None,
+            workspace: 1,
+            frame_before_fullscreen: None,
+        }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.frame_before_fullscreen.is_some()
+    }
+
+    /// Clamp `size` to this window's size constraints, like a real window
+    /// system would do.
+    fn constrain_size(&self, size: Size) -> Size {
+        let mut width = size.width.max(self.min_size.width);
+        let mut height = size.height.max(self.min_size.height);
+        if let Some(max_size) = self.max_size {
+            width = width.min(max_size.width);
+            height = height.min(max_size.height);
+        }
+
+        Size { width, height }
+    }
+}
+
+/// An in-memory desktop that implements [`WindowBackend`].
+#[derive(Debug, Clone)]
+pub struct SimulatedDesktop {
+    screens: Vec<SimulatedScreen>,
+    windows: BTreeMap<WindowId, SimulatedWindow>,
+    focused_window: Option<WindowId>,
+    /// Number of workspaces, workspace logical IDs are `1..=n_workspaces`.
+    n_workspaces: usize,
+    /// Logical ID of the current workspace.
+    current_workspace: usize,
+    next_window_id: u64,
+}
+
+impl SimulatedDesktop {
+    /// Create a desktop with these screens, 1 workspace and no window.
+    ///
+    /// The first screen is the main screen.  The [`DisplayId`] of a screen is
+    /// its index in this list.
+    pub fn new(screens: Vec<SimulatedScreen>) -> Self {
+        Self {
+            screens,
+            windows: BTreeMap::new(),
+            focused_window: None,
+            n_workspaces: 1,
+            current_workspace: 1,
+            next_window_id: 1,
+        }
+    }
+
+    pub fn screens(&self) -> &[SimulatedScreen] {
+        &self.screens
+    }
+
+    pub fn screens_mut(&mut self) -> &mut Vec<SimulatedScreen> {
+        &mut self.screens
+    }
+
+    /// Set the number of workspaces.
+    ///
+    /// Windows on the removed workspaces are moved to the last one.
+    pub fn set_workspace_count(&mut self, n_workspaces: usize) {
+        assert!(n_workspaces >= 1, "there should be at least 1 workspace");
+
+        self.n_workspaces = n_workspaces;
+        self.current_workspace = self.current_workspace.min(n_workspaces);
+        for window in self.windows.values_mut() {
+            window.workspace = window.workspace.min(n_workspaces);
+        }
+    }
+
+    pub fn workspace_count(&self) -> usize {
+        self.n_workspaces
+    }
+
+    /// Logical ID of the current workspace, starts from 1.
+    pub fn current_workspace(&self) -> usize {
+        self.current_workspace
+    }
+
+    /// Add a window and focus it.
+    pub fn add_window(&mut self, window: SimulatedWindow) -> WindowId {
+        assert!(
+            (1..=self.n_workspaces).contains(&window.workspace),
+            "window should be on an existing workspace"
+        );
+
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+        self.windows.insert(id, window);
+        self.set_focus(id);
+
+        id
+    }
+
+    /// Close the window, the focus is lost if it is the focused one.
+    pub fn close_window(&mut self, id: WindowId) -> Option<SimulatedWindow> {
+        if self.focused_window == Some(id) {
+            self.focused_window = None;
+        }
+
+        self.windows.remove(&id)
+    }
+
+    /// Focus the window, and switch to the workspace it is on.
+    ///
+    /// Return false if this window does not exist.
+    pub fn set_focus(&mut self, id: WindowId) -> bool {
+        let Some(window) = self.windows.get(&id) else {
+            return false;
+        };
+
+        self.current_workspace = window.workspace;
+        self.focused_window = Some(id);
+
+        true
+    }
+
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.focused_window
+    }
+
+    pub fn window(&self, id: WindowId) -> Option<&SimulatedWindow> {
+        self.windows.get(&id)
+    }
+
+    pub fn window_mut(&mut self, id: WindowId) -> Option<&mut SimulatedWindow> {
+        self.windows.get_mut(&id)
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = (WindowId, &SimulatedWindow)> {
+        self.windows.iter().map(|(id, window)| (*id, window))
+    }
+
+    fn frontmost_window(&self) -> Result<&SimulatedWindow, Error> {
+        self.focused_window
+            .and_then(|id| self.windows.get(&id))
+            .ok_or(Error::CannotFindFocusWindow)
+    }
+
+    fn frontmost_window_mut(&mut self) -> Result<&mut SimulatedWindow, Error> {
+        self.focused_window
+            .and_then(|id| self.windows.get_mut(&id))
+            .ok_or(Error::CannotFindFocusWindow)
+    }
+
+    /// Return the index of the screen which the frontmost window is on.
+    fn active_screen_index(&self) -> Result<usize, Error> {
+        // cast is safe, display IDs are indexes of `screens`
+        Ok(self.get_active_display_id()?.0 as usize)
+    }
+}
+
+impl WindowBackend for SimulatedDesktop {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        self.frontmost_window()?;
+        Ok(self.focused_window.expect("frontmost window exists"))
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        Ok(self.windows.keys().copied().collect())
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        if self.set_focus(window) {
+            Ok(())
+        } else {
+            Err(Error::NoSuchWindow)
+        }
+    }
+
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        Ok(self.frontmost_window()?.frame)
+    }
+
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        Ok(Some(self.frontmost_window()?.min_size))
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        let window = self.frontmost_window_mut()?;
+        window.frame = Rect {
+            origin: frame.origin,
+            size: window.constrain_size(frame.size),
+        };
+
+        Ok(())
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        self.frontmost_window_mut()?.frame.origin = origin;
+
+        Ok(())
+    }
+
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        Ok(self
+            .screens
+            .iter()
+            .enumerate()
+            .map(|(index, screen)| Display {
+                id: DisplayId(index as u64),
+                name: format!("Simulated {}", index),
+                frame: screen.frame,
+                reserved: Insets::between(&screen.frame, &screen.visible_frame),
+                scale_factor: 1.0,
+                rotation: 0,
+                is_primary: index == 0,
+            })
+            .collect())
+    }
+
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        if self.current_workspace == self.n_workspaces {
+            Ok(None)
+        } else {
+            Ok(Some(self.current_workspace + 1))
+        }
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        if self.current_workspace == 1 {
+            Ok(None)
+        } else {
+            Ok(Some(self.current_workspace - 1))
+        }
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        if space == 0 {
+            return Err(Error::NoSuchWorkspace);
+        }
+        if space > self.n_workspaces {
+            return Err(Error::NoSuchWorkspace);
+        }
+
+        // Like macOS, the window is dragged to that workspace, which then
+        // becomes the current one.
+        self.frontmost_window_mut()?.workspace = space;
+        self.current_workspace = space;
+
+        Ok(())
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        let screen_frame = self.screens[self.active_screen_index()?].frame;
+        let window = self.frontmost_window_mut()?;
+
+        match window.frame_before_fullscreen.take() {
+            Some(frame) => window.frame = frame,
+            None => {
+                window.frame_before_fullscreen = Some(window.frame);
+                window.frame = screen_frame;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,561 @@
+//! sway/i3 backend.
+//!
+//! It talks to the window manager over the [IPC protocol][ipc] shared by sway
+//! and i3.  Windows are made floating before moving or resizing them, or the
+//! tiling layout would override their frames.
+//!
+//! [ipc]: https://i3wm.org/docs/ipc.html
+
+use std::io::Read;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+const MAGIC: &[u8] = b"i3-ipc";
+
+/// Message types.
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const GET_OUTPUTS: u32 = 3;
+const GET_TREE: u32 = 4;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct IpcRect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl From<IpcRect> for Rect {
+    fn from(rect: IpcRect) -> Self {
+        Rect::new(
+            Point::new(rect.x as f64, rect.y as f64),
+            Size::new(rect.width as f64, rect.height as f64),
+        )
+    }
+}
+
+/// A node of the `GET_TREE` reply.
+#[derive(Debug, Deserialize)]
+struct Node {
+    id: i64,
+    #[serde(rename = "type")]
+    node_type: String,
+    focused: bool,
+    rect: IpcRect,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    floating_nodes: Vec<Node>,
+}
+
+impl Node {
+    /// Find the focused node in this subtree.
+    fn find_focused(&self) -> Option<&Node> {
+        if self.focused {
+            return Some(self);
+        }
+
+        self.nodes
+            .iter()
+            .chain(self.floating_nodes.iter())
+            .find_map(Node::find_focused)
+    }
+
+    fn is_window(&self) -> bool {
+        self.node_type == "con" || self.node_type == "floating_con"
+    }
+
+    /// Collect the IDs of the windows in this subtree, split containers are
+    /// not windows.
+    fn collect_window_ids(&self, ids: &mut Vec<i64>) {
+        if self.is_window() && self.nodes.is_empty() && self.floating_nodes.is_empty() {
+            ids.push(self.id);
+        }
+
+        for node in self.nodes.iter().chain(self.floating_nodes.iter()) {
+            node.collect_window_ids(ids);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    name: String,
+    active: bool,
+    #[serde(default)]
+    primary: bool,
+    rect: IpcRect,
+    #[serde(default)]
+    scale: Option<f64>,
+    /// e.g., `normal`, `90` or `flipped-90`, clockwise.
+    #[serde(default)]
+    transform: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Workspace {
+    num: i32,
+    visible: bool,
+    focused: bool,
+    rect: IpcRect,
+    output: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommandOutcome {
+    success: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FocusedWindow {
+    /// Container ID.
+    id: i64,
+    frame: Rect,
+}
+
+/// The sway/i3 backend.
+#[derive(Debug)]
+pub struct SwayBackend {
+    stream: UnixStream,
+}
+
+impl SwayBackend {
+    /// Connect to the socket specified by `$SWAYSOCK`, or `$I3SOCK` if it is
+    /// not set.
+    pub fn connect() -> Result<Self, Error> {
+        let path = std::env::var_os("SWAYSOCK")
+            .or_else(|| std::env::var_os("I3SOCK"))
+            .ok_or_else(|| Error::IpcError("neither $SWAYSOCK nor $I3SOCK is set".into()))?;
+
+        Self::connect_to(path)
+    }
+
+    /// Connect to the IPC socket at `path`.
+    pub fn connect_to(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let stream = UnixStream::connect(path)?;
+        Ok(Self { stream })
+    }
+
+    /// Send a message and deserialize the reply.
+    fn request<T: DeserializeOwned>(&self, message_type: u32, payload: &str) -> Result<T, Error> {
+        let payload_len: u32 = payload
+            .len()
+            .try_into()
+            .map_err(|_| Error::IpcError("payload is too long".into()))?;
+        let mut message = Vec::with_capacity(MAGIC.len() + 8 + payload.len());
+        message.extend_from_slice(MAGIC);
+        message.extend_from_slice(&payload_len.to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(payload.as_bytes());
+        (&self.stream).write_all(&message)?;
+
+        let mut header = [0_u8; MAGIC.len() + 8];
+        (&self.stream).read_exact(&mut header)?;
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err(Error::IpcError("invalid reply magic".into()));
+        }
+        let reply_len = u32::from_ne_bytes(header[6..10].try_into().expect("4 bytes"));
+        let reply_type = u32::from_ne_bytes(header[10..14].try_into().expect("4 bytes"));
+        let mut reply = vec![0_u8; reply_len as usize];
+        (&self.stream).read_exact(&mut reply)?;
+        if reply_type != message_type {
+            return Err(Error::IpcError(format!(
+                "expect reply of type {}, got {}",
+                message_type, reply_type
+            )));
+        }
+
+        serde_json::from_slice(&reply).map_err(|error| Error::IpcError(error.to_string()))
+    }
+
+    /// Run the commands, fail if any of them fails.
+    fn run_command(&self, command: &str) -> Result<(), Error> {
+        let outcomes: Vec<CommandOutcome> = self.request(RUN_COMMAND, command)?;
+        for outcome in outcomes {
+            if !outcome.success {
+                return Err(Error::IpcError(
+                    outcome
+                        .error
+                        .unwrap_or_else(|| format!("command [{}] failed", command)),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn focused_window(&self) -> Result<FocusedWindow, Error> {
+        let tree: Node = self.request(GET_TREE, "")?;
+        let node = tree
+            .find_focused()
+            // A workspace or output is focused when there is no window
+            .filter(|node| node.is_window())
+            .ok_or(Error::CannotFindFocusWindow)?;
+
+        Ok(FocusedWindow {
+            id: node.id,
+            frame: node.rect.into(),
+        })
+    }
+
+    fn focused_workspace(&self) -> Result<Workspace, Error> {
+        let workspaces: Vec<Workspace> = self.request(GET_WORKSPACES, "")?;
+        workspaces
+            .into_iter()
+            .find(|workspace| workspace.focused)
+            .ok_or_else(|| Error::IpcError("no focused workspace".into()))
+    }
+
+    /// List the active outputs, the primary one comes first.  The space
+    /// reserved on an output is what its frame has around the area of the
+    /// workspace it displays, i.e., the bars.
+    fn displays(&self) -> Result<Vec<Display>, Error> {
+        let mut outputs: Vec<Output> = self.request(GET_OUTPUTS, "")?;
+        outputs.retain(|output| output.active);
+        outputs.sort_by_key(|output| !output.primary);
+        let workspaces: Vec<Workspace> = self.request(GET_WORKSPACES, "")?;
+
+        let displays = outputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, output)| {
+                let frame = Rect::from(output.rect);
+                let visible_frame = workspaces
+                    .iter()
+                    .find(|workspace| workspace.visible && workspace.output == output.name)
+                    .map(|workspace| Rect::from(workspace.rect))
+                    .unwrap_or(frame);
+                let rotation = match output.transform.as_deref() {
+                    Some(transform) if transform.ends_with("90") => 90,
+                    Some(transform) if transform.ends_with("180") => 180,
+                    Some(transform) if transform.ends_with("270") => 270,
+                    _ => 0,
+                };
+
+                Display {
+                    id: DisplayId::from_name(&output.name),
+                    name: output.name,
+                    frame,
+                    reserved: Insets::between(&frame, &visible_frame),
+                    scale_factor: output.scale.filter(|scale| *scale > 0.0).unwrap_or(1.0),
+                    rotation,
+                    // i3 has a primary output, sway does not
+                    is_primary: output.primary || index == 0,
+                }
+            })
+            .collect();
+
+        Ok(displays)
+    }
+}
+
+impl WindowBackend for SwayBackend {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        // cast is safe, container IDs are positive
+        Ok(WindowId(self.focused_window()?.id as u64))
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        let tree: Node = self.request(GET_TREE, "")?;
+        let mut ids = Vec::new();
+        tree.collect_window_ids(&mut ids);
+
+        // cast is safe, container IDs are positive
+        Ok(ids.into_iter().map(|id| WindowId(id as u64)).collect())
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        if !self.list_window_ids()?.contains(&window) {
+            return Err(Error::NoSuchWindow);
+        }
+
+        self.run_command(&format!("[con_id={}] focus", window.0))
+    }
+
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        Ok(self.focused_window()?.frame)
+    }
+
+    /// sway and i3 do not expose it.
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        Ok(None)
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        let window = self.focused_window()?;
+        self.run_command(&format!(
+            "[con_id={}] floating enable, resize set width {} px height {} px, move absolute position {} px {} px",
+            window.id,
+            frame.size.width.round(),
+            frame.size.height.round(),
+            frame.origin.x.round(),
+            frame.origin.y.round(),
+        ))
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        let window = self.focused_window()?;
+        self.run_command(&format!(
+            "[con_id={}] floating enable, move absolute position {} px {} px",
+            window.id,
+            origin.x.round(),
+            origin.y.round(),
+        ))
+    }
+
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        self.displays()
+    }
+
+    /// Workspaces are created on demand, so there is always a next one.
+    /// `None` if the current workspace has no number.
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let num = self.focused_workspace()?.num;
+        // Named workspaces without a number have num -1
+        Ok(usize::try_from(num).ok().map(|num| num + 1))
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let num = self.focused_workspace()?.num;
+        Ok(usize::try_from(num)
+            .ok()
+            .filter(|num| *num > 1)
+            .map(|num| num - 1))
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        if space == 0 {
+            return Err(Error::NoSuchWorkspace);
+        }
+
+        let window = self.focused_window()?;
+        // Move the window to that workspace, then follow it, like what macOS does.
+        self.run_command(&format!(
+            "[con_id={id}] move container to workspace number {space}; workspace number {space}; [con_id={id}] focus",
+            id = window.id,
+            space = space,
+        ))
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        let window = self.focused_window()?;
+        self.run_command(&format!("[con_id={}] fullscreen toggle", window.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::unix::net::UnixListener;
+    use std::path::PathBuf;
+    use std::thread::JoinHandle;
+
+    use serde_json::json;
+    use serde_json::Value;
+
+    use crate::backend::test_dir;
+
+    /// Serve one client at a socket in `dir`, reply to every message with
+    /// `reply(message_type, payload)`, return the messages once the client is
+    /// gone.
+    fn serve(
+        dir: &Path,
+        reply: impl Fn(u32, &str) -> Value + Send + 'static,
+    ) -> (PathBuf, JoinHandle<Vec<(u32, String)>>) {
+        let path = dir.join("ipc.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut messages = Vec::new();
+            let mut header = [0_u8; MAGIC.len() + 8];
+            while stream.read_exact(&mut header).is_ok() {
+                assert_eq!(&header[..MAGIC.len()], MAGIC);
+                let len = u32::from_ne_bytes(header[6..10].try_into().unwrap());
+                let message_type = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+                let mut payload = vec![0_u8; len as usize];
+                stream.read_exact(&mut payload).unwrap();
+                let payload = String::from_utf8(payload).unwrap();
+
+                let body = reply(message_type, &payload).to_string();
+                let mut message = MAGIC.to_vec();
+                message.extend_from_slice(&(body.len() as u32).to_ne_bytes());
+                message.extend_from_slice(&message_type.to_ne_bytes());
+                message.extend_from_slice(body.as_bytes());
+                stream.write_all(&message).unwrap();
+
+                messages.push((message_type, payload));
+            }
+
+            messages
+        });
+
+        (path, server)
+    }
+
+    /// Output `eDP-1` with a 30px bar on top and a rotated `HDMI-1`, with the
+    /// focused window 42 on workspace 3.
+    fn reply(message_type: u32, payload: &str) -> Value {
+        match message_type {
+            RUN_COMMAND if payload.contains("fail") => {
+                json!([{ "success": false, "error": "cannot do that" }])
+            }
+            RUN_COMMAND => json!([{ "success": true }]),
+            GET_WORKSPACES => json!([
+                {
+                    "num": 3, "visible": true, "focused": true, "output": "eDP-1",
+                    "rect": { "x": 0, "y": 30, "width": 1920, "height": 1050 },
+                },
+                {
+                    "num": 7, "visible": true, "focused": false, "output": "HDMI-1",
+                    "rect": { "x": 1920, "y": 0, "width": 1080, "height": 1920 },
+                },
+            ]),
+            GET_OUTPUTS => json!([
+                {
+                    "name": "HDMI-1", "active": true, "scale": 1.5,
+                    "transform": "flipped-270",
+                    "rect": { "x": 1920, "y": 0, "width": 1080, "height": 1920 },
+                },
+                {
+                    "name": "eDP-1", "active": true, "primary": true, "scale": 2.0,
+                    "transform": "normal",
+                    "rect": { "x": 0, "y": 0, "width": 1920, "height": 1080 },
+                },
+                {
+                    "name": "DP-1", "active": false,
+                    "rect": { "x": 0, "y": 0, "width": 0, "height": 0 },
+                },
+            ]),
+            GET_TREE => json!({
+                "id": 1, "type": "root", "focused": false,
+                "rect": { "x": 0, "y": 0, "width": 3000, "height": 1920 },
+                "nodes": [{
+                    "id": 3, "type": "workspace", "focused": false,
+                    "rect": { "x": 0, "y": 30, "width": 1920, "height": 1050 },
+                    "nodes": [{
+                        "id": 42, "type": "con", "focused": true,
+                        "rect": { "x": 100, "y": 130, "width": 800, "height": 600 },
+                    }],
+                    "floating_nodes": [{
+                        "id": 43, "type": "floating_con", "focused": false,
+                        "rect": { "x": 0, "y": 0, "width": 10, "height": 10 },
+                    }],
+                }],
+            }),
+            _ => panic!("unexpected message type {}", message_type),
+        }
+    }
+
+    #[test]
+    fn commands() {
+        let dir = test_dir("sway-commands");
+        let (path, server) = serve(&dir, reply);
+        let mut backend = SwayBackend::connect_to(&path).unwrap();
+
+        backend
+            .set_frontmost_window_frame(Rect::new(Point::new(10.4, 20.6), Size::new(800.0, 599.5)))
+            .unwrap();
+        backend
+            .move_frontmost_window(Point::new(-5.0, 7.0))
+            .unwrap();
+        backend.move_frontmost_window_to_workspace(4).unwrap();
+        backend.toggle_fullscreen().unwrap();
+        backend.focus_window(WindowId(43)).unwrap();
+        assert!(matches!(
+            backend.focus_window(WindowId(3)),
+            Err(Error::NoSuchWindow)
+        ));
+        drop(backend);
+
+        let commands: Vec<String> = server
+            .join()
+            .unwrap()
+            .into_iter()
+            .filter(|(message_type, _)| *message_type == RUN_COMMAND)
+            .map(|(_, payload)| payload)
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                "[con_id=42] floating enable, resize set width 800 px height 600 px, move absolute position 10 px 21 px",
+                "[con_id=42] floating enable, move absolute position -5 px 7 px",
+                "[con_id=42] move container to workspace number 4; workspace number 4; [con_id=42] focus",
+                "[con_id=42] fullscreen toggle",
+                "[con_id=43] focus",
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_command() {
+        let dir = test_dir("sway-failed");
+        let (path, _server) = serve(&dir, |message_type, payload| {
+            reply(message_type, &payload.replace("fullscreen", "fail"))
+        });
+        let mut backend = SwayBackend::connect_to(&path).unwrap();
+
+        assert!(matches!(
+            backend.toggle_fullscreen(),
+            Err(Error::IpcError(error)) if error == "cannot do that"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn displays() {
+        let dir = test_dir("sway-displays");
+        let (path, _server) = serve(&dir, reply);
+        let backend = SwayBackend::connect_to(&path).unwrap();
+
+        let displays = backend.list_displays().unwrap();
+        // Inactive ones are left out, the primary one comes first
+        assert_eq!(displays.len(), 2);
+
+        assert_eq!(displays[0].name, "eDP-1");
+        assert_eq!(displays[0].id, DisplayId::from_name("eDP-1"));
+        assert!(displays[0].is_primary);
+        assert_eq!(displays[0].reserved, Insets::new(30.0, 0.0, 0.0, 0.0));
+        assert_eq!(displays[0].scale_factor, 2.0);
+        assert_eq!(displays[0].rotation, 0);
+
+        assert_eq!(displays[1].name, "HDMI-1");
+        assert!(!displays[1].is_primary);
+        assert_eq!(displays[1].reserved, Insets::ZERO);
+        assert_eq!(displays[1].scale_factor, 1.5);
+        assert_eq!(displays[1].rotation, 270);
+
+        assert_eq!(
+            backend.get_active_display_id().unwrap(),
+            DisplayId::from_name("eDP-1")
+        );
+        // Around workspace 3, workspace 7 is not the next one
+        assert_eq!(backend.get_next_workspace_logical_id().unwrap(), Some(4));
+        assert_eq!(
+            backend.get_previous_workspace_logical_id().unwrap(),
+            Some(2)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1115 @@
+//! X11 backend.
+//!
+//! It works with the window managers that implement the [EWMH][ewmh] spec,
+//! the focused window is found via `_NET_ACTIVE_WINDOW` and all the changes
+//! are sent to the window manager as client messages, which is what pagers
+//! and taskbars do.
+//!
+//! [ewmh]: https://specifications.freedesktop.org/wm-spec/latest/
+
+use std::time::Duration;
+
+use x11rb::connection::Connection;
+use x11rb::connection::RequestConnection;
+use x11rb::properties::WmSizeHints;
+use x11rb::protocol::randr;
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::xproto::Atom;
+use x11rb::protocol::xproto::AtomEnum;
+use x11rb::protocol::xproto::ClientMessageEvent;
+use x11rb::protocol::xproto::ConnectionExt as _;
+use x11rb::protocol::xproto::EventMask;
+use x11rb::protocol::xproto::MapState;
+use x11rb::protocol::xproto::Window;
+use x11rb::rust_connection::RustConnection;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::backend::WindowBackend;
+use crate::backend::WindowId;
+use crate::error::Error;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CURRENT_DESKTOP,
+        _NET_NUMBER_OF_DESKTOPS,
+        _NET_WORKAREA,
+        _NET_WM_STRUT,
+        _NET_WM_STRUT_PARTIAL,
+        _NET_WM_DESKTOP,
+        _NET_MOVERESIZE_WINDOW,
+        _NET_FRAME_EXTENTS,
+        _NET_WM_STATE,
+        _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_STATE_MAXIMIZED_VERT,
+        _NET_WM_STATE_MAXIMIZED_HORZ,
+    }
+}
+
+/// How long to wait for the window manager to focus a window.
+const FOCUS_POLL_ATTEMPTS: usize = 10;
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// `_NET_WM_STATE` actions.
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_TOGGLE: u32 = 2;
+
+/// Source indication of client messages, we are a pager-like tool.
+const SOURCE_PAGER: u32 = 2;
+
+/// `NorthWestGravity`, makes `_NET_MOVERESIZE_WINDOW` position the top-left
+/// corner of the window frame (decorations included).
+const GRAVITY_NORTH_WEST: u32 = 1;
+
+/// A monitor, in root window coordinates.
+#[derive(Debug, Clone)]
+struct Monitor {
+    name: String,
+    frame: Rect,
+    primary: bool,
+    /// Clockwise, in degrees.
+    rotation: u32,
+}
+
+/// An edge of the root window.
+#[derive(Debug, Clone, Copy)]
+enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// The area a dock or panel reserves along an edge of the root window, in
+/// root window coordinates.
+#[derive(Debug, Clone, Copy)]
+struct Strut {
+    edge: Edge,
+    area: Rect,
+}
+
+impl Strut {
+    /// Parse the 12 values of `_NET_WM_STRUT_PARTIAL`, `root` being the size
+    /// of the root window.
+    fn parse_partial(values: &[u32], root: Size) -> Vec<Strut> {
+        let [left, right, top, bottom] = std::array::from_fn(|i| values[i] as f64);
+        let [left_start_y, left_end_y, right_start_y, right_end_y] =
+            std::array::from_fn(|i| values[4 + i] as f64);
+        let [top_start_x, top_end_x, bottom_start_x, bottom_end_x] =
+            std::array::from_fn(|i| values[8 + i] as f64);
+        // The ends are inclusive
+        let candidates = [
+            (
+                Edge::Left,
+                Rect::new(
+                    Point::new(0.0, left_start_y),
+                    Size::new(left, left_end_y - left_start_y + 1.0),
+                ),
+            ),
+            (
+                Edge::Right,
+                Rect::new(
+                    Point::new(root.width - right, right_start_y),
+                    Size::new(right, right_end_y - right_start_y + 1.0),
+                ),
+            ),
+            (
+                Edge::Top,
+                Rect::new(
+                    Point::new(top_start_x, 0.0),
+                    Size::new(top_end_x - top_start_x + 1.0, top),
+                ),
+            ),
+            (
+                Edge::Bottom,
+                Rect::new(
+                    Point::new(bottom_start_x, root.height - bottom),
+                    Size::new(bottom_end_x - bottom_start_x + 1.0, bottom),
+                ),
+            ),
+        ];
+
+        candidates
+            .into_iter()
+            .filter(|(_, area)| area.area() > 0.0)
+            .map(|(edge, area)| Strut { edge, area })
+            .collect()
+    }
+
+    /// Parse the 4 values of `_NET_WM_STRUT`, which span the whole edges.
+    fn parse(values: &[u32], root: Size) -> Vec<Strut> {
+        let spans = [0, u32::MAX].repeat(4);
+
+        Self::parse_partial(&[&values[..4], &spans].concat(), root)
+    }
+}
+
+/// The space that `struts` reserve on the monitor at `frame`.
+fn reserved_by_struts(frame: &Rect, struts: &[Strut]) -> Insets {
+    let mut reserved = Insets::ZERO;
+    for strut in struts {
+        let Some(overlap) = strut.area.intersection(frame) else {
+            continue;
+        };
+        match strut.edge {
+            Edge::Left => reserved.left = reserved.left.max(overlap.max().x - frame.min().x),
+            Edge::Right => reserved.right = reserved.right.max(frame.max().x - overlap.min().x),
+            Edge::Top => reserved.top = reserved.top.max(overlap.max().y - frame.min().y),
+            Edge::Bottom => reserved.bottom = reserved.bottom.max(frame.max().y - overlap.min().y),
+        }
+    }
+
+    reserved
+}
+
+/// The X11 backend.
+#[derive(Debug)]
+pub struct X11Backend {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl X11Backend {
+    /// Connect to the X server specified by the `$DISPLAY` environment variable.
+    pub fn connect() -> Result<Self, Error> {
+        Self::connect_to(None)
+    }
+
+    /// Connect to the X server `display`, e.g., `":1"`, `None` means `$DISPLAY`.
+    pub fn connect_to(display: Option<&str>) -> Result<Self, Error> {
+        let (conn, screen_num) = x11rb::connect(display).map_err(Error::X11ConnectError)?;
+
+        Self::with_connection(conn, screen_num)
+    }
+
+    /// Use `conn`, on its screen `screen_num`.
+    fn with_connection(conn: RustConnection, screen_num: usize) -> Result<Self, Error> {
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = Atoms::new(&conn)?.reply()?;
+
+        Ok(Self { conn, root, atoms })
+    }
+
+    /// Read a property whose format is 32, `None` if it does not exist.
+    fn get_property32(
+        &self,
+        window: Window,
+        property: Atom,
+        type_: impl Into<Atom>,
+    ) -> Result<Option<Vec<u32>>, Error> {
+        let reply = self
+            .conn
+            .get_property(false, window, property, type_, 0, u32::MAX)?
+            .reply()?;
+
+        Ok(reply.value32().map(|values| values.collect()))
+    }
+
+    /// Read the first value of a 32-bit root window property.
+    fn get_root_cardinal(&self, property: Atom) -> Result<Option<u32>, Error> {
+        Ok(self
+            .get_property32(self.root, property, AtomEnum::CARDINAL)?
+            .and_then(|values| values.first().copied()))
+    }
+
+    fn frontmost_window(&self) -> Result<Window, Error> {
+        let window = self
+            .get_property32(self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW)?
+            .and_then(|values| values.first().copied())
+            .unwrap_or(x11rb::NONE);
+        if window == x11rb::NONE {
+            return Err(Error::CannotFindFocusWindow);
+        }
+
+        Ok(window)
+    }
+
+    /// Send a client message to the root window, which is how EWMH asks the
+    /// window manager to do things.
+    fn send_client_message(
+        &self,
+        window: Window,
+        message_type: Atom,
+        data: [u32; 5],
+    ) -> Result<(), Error> {
+        let event = ClientMessageEvent::new(32, window, message_type, data);
+        self.conn.send_event(
+            false,
+            self.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Return the decorations' size, as `[left, right, top, bottom]`.
+    fn frame_extents(&self, window: Window) -> Result<[f64; 4], Error> {
+        let extents = self
+            .get_property32(window, self.atoms._NET_FRAME_EXTENTS, AtomEnum::CARDINAL)?
+            .unwrap_or_default();
+        let extent = |idx: usize| extents.get(idx).copied().unwrap_or(0) as f64;
+
+        Ok([extent(0), extent(1), extent(2), extent(3)])
+    }
+
+    /// Window managers ignore geometry requests of maximized windows, so
+    /// unmaximize it first.
+    fn unmaximize(&self, window: Window) -> Result<(), Error> {
+        let states = self
+            .get_property32(window, self.atoms._NET_WM_STATE, AtomEnum::ATOM)?
+            .unwrap_or_default();
+        let maximized_vert = self.atoms._NET_WM_STATE_MAXIMIZED_VERT;
+        let maximized_horz = self.atoms._NET_WM_STATE_MAXIMIZED_HORZ;
+        if !states.contains(&maximized_vert) && !states.contains(&maximized_horz) {
+            return Ok(());
+        }
+
+        self.send_client_message(
+            window,
+            self.atoms._NET_WM_STATE,
+            [
+                NET_WM_STATE_REMOVE,
+                maximized_vert,
+                maximized_horz,
+                SOURCE_PAGER,
+                0,
+            ],
+        )
+    }
+
+    /// Send `_NET_MOVERESIZE_WINDOW`, `origin` and `size` include the
+    /// decorations.  The size is untouched if `size` is `None`.
+    fn move_resize(&self, window: Window, origin: Point, size: Option<Size>) -> Result<(), Error> {
+        self.unmaximize(window)?;
+
+        let [left, right, top, bottom] = self.frame_extents(window)?;
+        // x, y
+        let mut flags = GRAVITY_NORTH_WEST | (1 << 8) | (1 << 9) | (SOURCE_PAGER << 12);
+        let (mut width, mut height) = (0, 0);
+        if let Some(size) = size {
+            // width, height
+            flags |= (1 << 10) | (1 << 11);
+            // They are the client size, exclude the decorations.
+            width = (size.width - left - right).round().max(1.0) as u32;
+            height = (size.height - top - bottom).round().max(1.0) as u32;
+        }
+
+        self.send_client_message(
+            window,
+            self.atoms._NET_MOVERESIZE_WINDOW,
+            [
+                flags,
+                origin.x.round() as i32 as u32,
+                origin.y.round() as i32 as u32,
+                width,
+                height,
+            ],
+        )
+    }
+
+    /// Index of the current desktop, starts from 0.
+    fn current_desktop(&self) -> Result<usize, Error> {
+        Ok(self
+            .get_root_cardinal(self.atoms._NET_CURRENT_DESKTOP)?
+            .unwrap_or(0) as usize)
+    }
+
+    fn number_of_desktops(&self) -> Result<usize, Error> {
+        Ok(self
+            .get_root_cardinal(self.atoms._NET_NUMBER_OF_DESKTOPS)?
+            .unwrap_or(1) as usize)
+    }
+
+    /// The monitors, from XRandR, the primary one comes first.  Fall back to
+    /// the root window if XRandR is not available.
+    fn monitors(&self) -> Result<Vec<Monitor>, Error> {
+        let has_randr = self
+            .conn
+            .extension_information(randr::X11_EXTENSION_NAME)?
+            .is_some();
+        if has_randr {
+            let mut infos = self
+                .conn
+                .randr_get_monitors(self.root, true)?
+                .reply()?
+                .monitors;
+            // The primary monitor is the main screen
+            infos.sort_by_key(|info| !info.primary);
+
+            let monitors = infos
+                .iter()
+                .map(|info| {
+                    let name = self.conn.get_atom_name(info.name)?.reply()?.name;
+                    Ok(Monitor {
+                        name: String::from_utf8_lossy(&name).into_owned(),
+                        frame: Rect::new(
+                            Point::new(info.x as f64, info.y as f64),
+                            Size::new(info.width as f64, info.height as f64),
+                        ),
+                        primary: info.primary,
+                        rotation: self.monitor_rotation(info)?,
+                    })
+                })
+                .collect::<Result<Vec<Monitor>, Error>>()?;
+            if !monitors.is_empty() {
+                return Ok(monitors);
+            }
+        }
+
+        let geometry = self.conn.get_geometry(self.root)?.reply()?;
+        Ok(vec![Monitor {
+            name: "default".into(),
+            frame: Rect::new(
+                Point::ZERO,
+                Size::new(geometry.width as f64, geometry.height as f64),
+            ),
+            primary: true,
+            rotation: 0,
+        }])
+    }
+
+    /// The rotation of the CRTC that drives the first output of `monitor`, 0
+    /// if there is none.
+    fn monitor_rotation(&self, monitor: &randr::MonitorInfo) -> Result<u32, Error> {
+        let Some(&output) = monitor.outputs.first() else {
+            return Ok(0);
+        };
+        let crtc = self.conn.randr_get_output_info(output, 0)?.reply()?.crtc;
+        if crtc == 0 {
+            return Ok(0);
+        }
+        let rotation = self.conn.randr_get_crtc_info(crtc, 0)?.reply()?.rotation;
+
+        // RandR rotates counter-clockwise
+        let rotation = if rotation.contains(randr::Rotation::ROTATE90) {
+            270
+        } else if rotation.contains(randr::Rotation::ROTATE180) {
+            180
+        } else if rotation.contains(randr::Rotation::ROTATE270) {
+            90
+        } else {
+            0
+        };
+
+        Ok(rotation)
+    }
+
+    /// The struts of the visible windows, from `_NET_WM_STRUT_PARTIAL`, or
+    /// `_NET_WM_STRUT` for older clients.
+    ///
+    /// Docks and panels are often not managed, so the children of the root
+    /// window are looked at as well as `_NET_CLIENT_LIST`.
+    fn struts(&self) -> Result<Vec<Strut>, Error> {
+        let geometry = self.conn.get_geometry(self.root)?.reply()?;
+        let root = Size::new(geometry.width as f64, geometry.height as f64);
+
+        let mut windows = self
+            .get_property32(self.root, self.atoms._NET_CLIENT_LIST, AtomEnum::WINDOW)?
+            .unwrap_or_default();
+        windows.extend(self.conn.query_tree(self.root)?.reply()?.children);
+        windows.sort_unstable();
+        windows.dedup();
+
+        // Send all the requests before waiting for any reply
+        let cookies = windows
+            .into_iter()
+            .map(|window| {
+                Ok((
+                    self.conn.get_window_attributes(window)?,
+                    self.conn.get_property(
+                        false,
+                        window,
+                        self.atoms._NET_WM_STRUT_PARTIAL,
+                        AtomEnum::CARDINAL,
+                        0,
+                        12,
+                    )?,
+                    self.conn.get_property(
+                        false,
+                        window,
+                        self.atoms._NET_WM_STRUT,
+                        AtomEnum::CARDINAL,
+                        0,
+                        4,
+                    )?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut struts = Vec::new();
+        for (attributes, partial, full) in cookies {
+            // Windows can be gone by now, they have no struts then
+            let viewable = attributes
+                .reply()
+                .is_ok_and(|attributes| attributes.map_state == MapState::VIEWABLE);
+            let partial: Option<Vec<u32>> = partial
+                .reply()
+                .ok()
+                .and_then(|reply| reply.value32().map(Iterator::collect));
+            let full: Option<Vec<u32>> = full
+                .reply()
+                .ok()
+                .and_then(|reply| reply.value32().map(Iterator::collect));
+            if !viewable {
+                continue;
+            }
+
+            match (partial, full) {
+                (Some(values), _) if values.len() == 12 => {
+                    struts.extend(Strut::parse_partial(&values, root))
+                }
+                (_, Some(values)) if values.len() == 4 => {
+                    struts.extend(Strut::parse(&values, root))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(struts)
+    }
+
+    /// List the displays.  The space reserved on a display is computed from
+    /// the struts of docks and panels.  Without struts, e.g., when the window
+    /// manager draws its own panel, it is what the work area (`_NET_WORKAREA`)
+    /// of the current desktop leaves out, which is only right for the outer
+    /// edges of the monitors.
+    fn displays(&self) -> Result<Vec<Display>, Error> {
+        let struts = self.struts()?;
+        let work_area = self
+            .get_property32(self.root, self.atoms._NET_WORKAREA, AtomEnum::CARDINAL)?
+            .and_then(|values| {
+                let current_desktop = self.current_desktop().ok()?;
+                let area = values.chunks_exact(4).nth(current_desktop)?;
+                Some(Rect::new(
+                    Point::new(area[0] as f64, area[1] as f64),
+                    Size::new(area[2] as f64, area[3] as f64),
+                ))
+            });
+
+        let displays = self
+            .monitors()?
+            .into_iter()
+            .map(|monitor| {
+                let reserved = if struts.is_empty() {
+                    work_area
+                        .and_then(|work_area| monitor.frame.intersection(&work_area))
+                        .map(|visible_frame| Insets::between(&monitor.frame, &visible_frame))
+                        .unwrap_or(Insets::ZERO)
+                } else {
+                    reserved_by_struts(&monitor.frame, &struts)
+                };
+                Display {
+                    id: DisplayId::from_name(&monitor.name),
+                    name: monitor.name,
+                    frame: monitor.frame,
+                    reserved,
+                    // X11 has no per-monitor scaling
+                    scale_factor: 1.0,
+                    rotation: monitor.rotation,
+                    is_primary: monitor.primary,
+                }
+            })
+            .collect();
+
+        Ok(displays)
+    }
+}
+
+impl WindowBackend for X11Backend {
+    fn get_frontmost_window_id(&self) -> Result<WindowId, Error> {
+        self.frontmost_window()
+            .map(|window| WindowId(window.into()))
+    }
+
+    fn list_window_ids(&self) -> Result<Vec<WindowId>, Error> {
+        Ok(self
+            .get_property32(self.root, self.atoms._NET_CLIENT_LIST, AtomEnum::WINDOW)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|window| WindowId(window.into()))
+            .collect())
+    }
+
+    fn focus_window(&mut self, window: WindowId) -> Result<(), Error> {
+        if !self.list_window_ids()?.contains(&window) {
+            return Err(Error::NoSuchWindow);
+        }
+        // cast is safe, it is from _NET_CLIENT_LIST
+        let window = window.0 as Window;
+
+        self.send_client_message(
+            window,
+            self.atoms._NET_ACTIVE_WINDOW,
+            [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0],
+        )?;
+
+        // The window manager does it asynchronously, give it a moment
+        for _ in 0..FOCUS_POLL_ATTEMPTS {
+            if self.frontmost_window().ok() == Some(window) {
+                break;
+            }
+            std::thread::sleep(FOCUS_POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    fn get_frontmost_window_frame(&self) -> Result<Rect, Error> {
+        let window = self.frontmost_window()?;
+        let geometry = self.conn.get_geometry(window)?.reply()?;
+        let position = self
+            .conn
+            .translate_coordinates(window, self.root, 0, 0)?
+            .reply()?;
+        let [left, right, top, bottom] = self.frame_extents(window)?;
+
+        Ok(Rect::new(
+            Point::new(position.dst_x as f64 - left, position.dst_y as f64 - top),
+            Size::new(
+                geometry.width as f64 + left + right,
+                geometry.height as f64 + top + bottom,
+            ),
+        ))
+    }
+
+    /// From the `WM_NORMAL_HINTS` set by the client, plus the frame
+    /// extents.
+    fn get_frontmost_window_min_size(&self) -> Result<Option<Size>, Error> {
+        let window = self.frontmost_window()?;
+        let hints = WmSizeHints::get_normal_hints(&self.conn, window)?.reply()?;
+        let Some((min_width, min_height)) = hints.and_then(|hints| hints.min_size) else {
+            return Ok(None);
+        };
+        let [left, right, top, bottom] = self.frame_extents(window)?;
+
+        Ok(Some(Size::new(
+            min_width as f64 + left + right,
+            min_height as f64 + top + bottom,
+        )))
+    }
+
+    fn set_frontmost_window_frame(&mut self, frame: Rect) -> Result<(), Error> {
+        let window = self.frontmost_window()?;
+        self.move_resize(window, frame.origin, Some(frame.size))
+    }
+
+    fn move_frontmost_window(&mut self, origin: Point) -> Result<(), Error> {
+        let window = self.frontmost_window()?;
+        self.move_resize(window, origin, None)
+    }
+
+    fn list_displays(&self) -> Result<Vec<Display>, Error> {
+        self.displays()
+    }
+
+    fn get_next_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let current_desktop = self.current_desktop()?;
+        if current_desktop + 1 >= self.number_of_desktops()? {
+            Ok(None)
+        } else {
+            // Logical ID starts from 1
+            Ok(Some(current_desktop + 2))
+        }
+    }
+
+    fn get_previous_workspace_logical_id(&self) -> Result<Option<usize>, Error> {
+        let current_desktop = self.current_desktop()?;
+        if current_desktop == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(current_desktop))
+        }
+    }
+
+    fn move_frontmost_window_to_workspace(&mut self, space: usize) -> Result<(), Error> {
+        if space == 0 {
+            return Err(Error::NoSuchWorkspace);
+        }
+        if space > self.number_of_desktops()? {
+            return Err(Error::NoSuchWorkspace);
+        }
+
+        let window = self.frontmost_window()?;
+        // cast is safe as space is at most _NET_NUMBER_OF_DESKTOPS, a u32
+        let desktop = (space - 1) as u32;
+
+        // Move the window to that desktop, then follow it, like what macOS does.
+        self.send_client_message(
+            window,
+            self.atoms._NET_WM_DESKTOP,
+            [desktop, SOURCE_PAGER, 0, 0, 0],
+        )?;
+        self.send_client_message(
+            self.root,
+            self.atoms._NET_CURRENT_DESKTOP,
+            [desktop, x11rb::CURRENT_TIME, 0, 0, 0],
+        )?;
+        self.send_client_message(
+            window,
+            self.atoms._NET_ACTIVE_WINDOW,
+            [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0],
+        )
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), Error> {
+        let window = self.frontmost_window()?;
+        self.send_client_message(
+            window,
+            self.atoms._NET_WM_STATE,
+            [
+                NET_WM_STATE_TOGGLE,
+                self.atoms._NET_WM_STATE_FULLSCREEN,
+                0,
+                SOURCE_PAGER,
+                0,
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use x11rb::protocol::xproto::GetGeometryReply;
+    use x11rb::protocol::xproto::GetInputFocusReply;
+    use x11rb::protocol::xproto::GetPropertyReply;
+    use x11rb::protocol::xproto::GetWindowAttributesReply;
+    use x11rb::protocol::xproto::InternAtomReply;
+    use x11rb::protocol::xproto::QueryExtensionReply;
+    use x11rb::protocol::xproto::QueryTreeReply;
+    use x11rb::protocol::xproto::Screen;
+    use x11rb::protocol::xproto::Setup;
+    use x11rb::protocol::xproto::TranslateCoordinatesReply;
+    use x11rb::rust_connection::DefaultStream;
+    use x11rb::x11_utils::Serialize;
+    use x11rb::x11_utils::TryParse;
+
+    use crate::backend::test_dir;
+
+    const ROOT: Window = 0x100;
+    const ACTIVE: Window = 0x400001;
+    const OTHER: Window = 0x400002;
+    /// A mapped dock with a 30px top strut.
+    const DOCK: Window = 0x600001;
+    /// An unmapped dock with a 100px left strut.
+    const HIDDEN_DOCK: Window = 0x600002;
+    /// A mapped dock with a legacy 40px bottom strut.
+    const OLD_DOCK: Window = 0x600003;
+
+    /// A client message sent to the root window: the window it is about,
+    /// the name of its type and its data.
+    type Message = (Window, String, [u32; 5]);
+
+    /// A fake X server with a 1920x1080 screen without XRandR.
+    ///
+    /// The active window is 400x300 at (102, 120) with 2px borders and a
+    /// 20px title bar, maximized, on the second of 3 desktops.
+    struct Server {
+        atoms: HashMap<String, Atom>,
+        properties: HashMap<(Window, String), (Atom, Vec<u32>)>,
+        messages: Arc<Mutex<Vec<Message>>>,
+    }
+
+    impl Server {
+        fn new(messages: Arc<Mutex<Vec<Message>>>) -> Self {
+            let mut server = Self {
+                atoms: HashMap::from([("WM_NORMAL_HINTS".to_string(), 40)]),
+                properties: HashMap::new(),
+                messages,
+            };
+            let cardinal = AtomEnum::CARDINAL.into();
+            let window = AtomEnum::WINDOW.into();
+            let maximized = vec![
+                server.atom("_NET_WM_STATE_MAXIMIZED_VERT"),
+                server.atom("_NET_WM_STATE_MAXIMIZED_HORZ"),
+            ];
+            // Minimum size of 200x100
+            let mut hints = vec![0; 18];
+            hints[0] = 1 << 4;
+            hints[5] = 200;
+            hints[6] = 100;
+
+            for (owner, name, type_, values) in [
+                (ROOT, "_NET_ACTIVE_WINDOW", window, vec![ACTIVE]),
+                (ROOT, "_NET_CLIENT_LIST", window, vec![ACTIVE, OTHER]),
+                (ROOT, "_NET_CURRENT_DESKTOP", cardinal, vec![1]),
+                (ROOT, "_NET_NUMBER_OF_DESKTOPS", cardinal, vec![3]),
+                (ACTIVE, "_NET_FRAME_EXTENTS", cardinal, vec![2, 2, 20, 2]),
+                (ACTIVE, "_NET_WM_STATE", AtomEnum::ATOM.into(), maximized),
+                (
+                    ACTIVE,
+                    "WM_NORMAL_HINTS",
+                    AtomEnum::WM_SIZE_HINTS.into(),
+                    hints,
+                ),
+                (
+                    DOCK,
+                    "_NET_WM_STRUT_PARTIAL",
+                    cardinal,
+                    vec![0, 0, 30, 0, 0, 0, 0, 0, 0, 1919, 0, 0],
+                ),
+                (
+                    HIDDEN_DOCK,
+                    "_NET_WM_STRUT_PARTIAL",
+                    cardinal,
+                    vec![100, 0, 0, 0, 0, 1079, 0, 0, 0, 0, 0, 0],
+                ),
+                (OLD_DOCK, "_NET_WM_STRUT", cardinal, vec![0, 0, 0, 40]),
+            ] {
+                server
+                    .properties
+                    .insert((owner, name.to_string()), (type_, values));
+            }
+
+            server
+        }
+
+        /// Intern `name`.
+        fn atom(&mut self, name: &str) -> Atom {
+            let next = 0x200 + self.atoms.len() as Atom;
+            *self.atoms.entry(name.to_string()).or_insert(next)
+        }
+
+        fn atom_name(&self, atom: Atom) -> String {
+            self.atoms
+                .iter()
+                .find(|(_, &value)| value == atom)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| panic!("unknown atom {}", atom))
+        }
+
+        /// Handle the connection setup, then every request on `stream`.
+        fn serve(mut self, mut stream: UnixStream) {
+            // No authorization, so the setup request is just its header
+            let mut setup_request = [0_u8; 12];
+            stream.read_exact(&mut setup_request).unwrap();
+            let setup = Setup {
+                status: 1,
+                protocol_major_version: 11,
+                resource_id_base: 0x200000,
+                resource_id_mask: 0x1fffff,
+                maximum_request_length: u16::MAX,
+                roots: vec![Screen {
+                    root: ROOT,
+                    width_in_pixels: 1920,
+                    height_in_pixels: 1080,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            let mut setup = setup.serialize();
+            let length = ((setup.len() - 8) / 4) as u16;
+            setup[6..8].copy_from_slice(&length.to_ne_bytes());
+            stream.write_all(&setup).unwrap();
+
+            for sequence in 1.. {
+                let mut header = [0_u8; 4];
+                if stream.read_exact(&mut header).is_err() {
+                    // Disconnected
+                    return;
+                }
+                let length = u16::from_ne_bytes([header[2], header[3]]) as usize * 4;
+                let mut request = header.to_vec();
+                request.resize(length, 0);
+                stream.read_exact(&mut request[4..]).unwrap();
+
+                if let Some(mut reply) = self.reply(&request, sequence as u16) {
+                    reply.resize(reply.len().max(32).next_multiple_of(4), 0);
+                    let length = ((reply.len() - 32) / 4) as u32;
+                    reply[4..8].copy_from_slice(&length.to_ne_bytes());
+                    stream.write_all(&reply).unwrap();
+                }
+            }
+        }
+
+        /// The reply to `request`, `None` if it has none.
+        fn reply(&mut self, request: &[u8], sequence: u16) -> Option<Vec<u8>> {
+            let u16_at = |offset: usize| u16::from_ne_bytes([request[offset], request[offset + 1]]);
+            let u32_at =
+                |offset: usize| u32::from_ne_bytes(request[offset..offset + 4].try_into().unwrap());
+
+            let reply = match request[0] {
+                // GetWindowAttributes
+                3 => GetWindowAttributesReply {
+                    sequence,
+                    map_state: if u32_at(4) == HIDDEN_DOCK {
+                        MapState::UNMAPPED
+                    } else {
+                        MapState::VIEWABLE
+                    },
+                    ..Default::default()
+                }
+                .serialize()
+                .to_vec(),
+                // GetGeometry
+                14 => {
+                    let (width, height) = match u32_at(4) {
+                        ROOT => (1920, 1080),
+                        ACTIVE => (400, 300),
+                        window => panic!("unexpected window {:#x}", window),
+                    };
+                    GetGeometryReply {
+                        sequence,
+                        root: ROOT,
+                        width,
+                        height,
+                        ..Default::default()
+                    }
+                    .serialize()
+                    .to_vec()
+                }
+                // QueryTree, docks are not managed
+                15 => QueryTreeReply {
+                    sequence,
+                    root: ROOT,
+                    children: vec![DOCK, HIDDEN_DOCK, OLD_DOCK],
+                    ..Default::default()
+                }
+                .serialize(),
+                // InternAtom
+                16 => {
+                    let name = &request[8..8 + u16_at(4) as usize];
+                    InternAtomReply {
+                        sequence,
+                        atom: self.atom(std::str::from_utf8(name).unwrap()),
+                        ..Default::default()
+                    }
+                    .serialize()
+                    .to_vec()
+                }
+                // GetProperty
+                20 => {
+                    let name = self.atom_name(u32_at(8));
+                    let long_length = u32_at(20) as usize;
+                    let (format, type_, values) = match self.properties.get(&(u32_at(4), name)) {
+                        Some((type_, values)) => {
+                            (32, *type_, &values[..long_length.min(values.len())])
+                        }
+                        None => (0, x11rb::NONE, &[][..]),
+                    };
+                    GetPropertyReply {
+                        format,
+                        sequence,
+                        type_,
+                        value_len: values.len() as u32,
+                        value: values
+                            .iter()
+                            .flat_map(|value| value.to_ne_bytes())
+                            .collect(),
+                        ..Default::default()
+                    }
+                    .serialize()
+                }
+                // SendEvent
+                25 => {
+                    assert_eq!(u32_at(4), ROOT);
+                    let (event, _) = ClientMessageEvent::try_parse(&request[12..44]).unwrap();
+                    let message = (
+                        event.window,
+                        self.atom_name(event.type_),
+                        event.data.as_data32(),
+                    );
+                    self.messages.lock().unwrap().push(message);
+                    return None;
+                }
+                // TranslateCoordinates
+                40 => {
+                    assert_eq!((u32_at(4), u32_at(8)), (ACTIVE, ROOT));
+                    TranslateCoordinatesReply {
+                        same_screen: true,
+                        sequence,
+                        dst_x: 102,
+                        dst_y: 120,
+                        ..Default::default()
+                    }
+                    .serialize()
+                    .to_vec()
+                }
+                // GetInputFocus, to sync
+                43 => GetInputFocusReply {
+                    sequence,
+                    ..Default::default()
+                }
+                .serialize()
+                .to_vec(),
+                // QueryExtension, there is none
+                98 => QueryExtensionReply {
+                    sequence,
+                    present: false,
+                    ..Default::default()
+                }
+                .serialize()
+                .to_vec(),
+                opcode => panic!("unexpected request {}", opcode),
+            };
+
+            Some(reply)
+        }
+    }
+
+    /// Start a fake X server on a socket in `dir` and connect to it.
+    fn connect(dir: &Path) -> (X11Backend, Arc<Mutex<Vec<Message>>>) {
+        let path = dir.join("X0");
+        let listener = UnixListener::bind(&path).unwrap();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let server = Server::new(messages.clone());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            server.serve(stream);
+        });
+
+        let (stream, _) =
+            DefaultStream::from_unix_stream(UnixStream::connect(&path).unwrap()).unwrap();
+        let conn = RustConnection::connect_to_stream(stream, 0).unwrap();
+
+        (X11Backend::with_connection(conn, 0).unwrap(), messages)
+    }
+
+    #[test]
+    fn client_messages() {
+        let dir = test_dir("x11-client-messages");
+        let (mut backend, messages) = connect(&dir);
+        let atoms = backend.atoms;
+
+        backend
+            .set_frontmost_window_frame(Rect::new(Point::new(10.4, 20.6), Size::new(804.0, 622.0)))
+            .unwrap();
+        backend
+            .move_frontmost_window(Point::new(-5.0, 7.0))
+            .unwrap();
+        backend.move_frontmost_window_to_workspace(3).unwrap();
+        backend.toggle_fullscreen().unwrap();
+        assert!(matches!(
+            backend.move_frontmost_window_to_workspace(4),
+            Err(Error::NoSuchWorkspace)
+        ));
+        // Also makes sure that the server has seen all the messages
+        assert!(matches!(
+            backend.focus_window(WindowId(0x999)),
+            Err(Error::NoSuchWindow)
+        ));
+
+        let unmaximize = (
+            ACTIVE,
+            "_NET_WM_STATE".to_string(),
+            [
+                0,
+                atoms._NET_WM_STATE_MAXIMIZED_VERT,
+                atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+                2,
+                0,
+            ],
+        );
+        // North west gravity, from a pager, with x and y, then width and height
+        let flags = 1 | (2 << 12) | (1 << 8) | (1 << 9);
+        let size_flags = (1 << 10) | (1 << 11);
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                unmaximize.clone(),
+                (
+                    ACTIVE,
+                    "_NET_MOVERESIZE_WINDOW".to_string(),
+                    [flags | size_flags, 10, 21, 800, 600]
+                ),
+                unmaximize,
+                (
+                    ACTIVE,
+                    "_NET_MOVERESIZE_WINDOW".to_string(),
+                    [flags, -5_i32 as u32, 7, 0, 0]
+                ),
+                (ACTIVE, "_NET_WM_DESKTOP".to_string(), [2, 2, 0, 0, 0]),
+                (ROOT, "_NET_CURRENT_DESKTOP".to_string(), [2, 0, 0, 0, 0]),
+                (ACTIVE, "_NET_ACTIVE_WINDOW".to_string(), [2, 0, 0, 0, 0]),
+                (
+                    ACTIVE,
+                    "_NET_WM_STATE".to_string(),
+                    [2, atoms._NET_WM_STATE_FULLSCREEN, 0, 2, 0]
+                ),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn window_and_displays() {
+        let dir = test_dir("x11-window-and-displays");
+        let (backend, _) = connect(&dir);
+
+        assert_eq!(
+            backend.get_frontmost_window_id().unwrap(),
+            WindowId(ACTIVE.into())
+        );
+        assert_eq!(
+            backend.list_window_ids().unwrap(),
+            [WindowId(ACTIVE.into()), WindowId(OTHER.into())]
+        );
+        // Decorations included
+        assert_eq!(
+            backend.get_frontmost_window_frame().unwrap(),
+            Rect::new(Point::new(100.0, 100.0), Size::new(404.0, 322.0))
+        );
+        assert_eq!(
+            backend.get_frontmost_window_min_size().unwrap(),
+            Some(Size::new(204.0, 122.0))
+        );
+        assert_eq!(backend.get_next_workspace_logical_id().unwrap(), Some(3));
+        assert_eq!(
+            backend.get_previous_workspace_logical_id().unwrap(),
+            Some(1)
+        );
+
+        // The root window, minus the struts of the mapped docks
+        let displays = backend.list_displays().unwrap();
+        assert_eq!(displays.len(), 1);
+        assert_eq!(displays[0].name, "default");
+        assert!(displays[0].is_primary);
+        assert_eq!(
+            displays[0].frame,
+            Rect::new(Point::ZERO, Size::new(1920.0, 1080.0))
+        );
+        assert_eq!(displays[0].reserved, Insets::new(30.0, 0.0, 40.0, 0.0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn struts_on_side_by_side_monitors() {
+        let root = Size::new(3840.0, 1080.0);
+        let left = Rect::new(Point::ZERO, Size::new(1920.0, 1080.0));
+        let right = Rect::new(Point::new(1920.0, 0.0), Size::new(1920.0, 1080.0));
+        // A 30px panel at the top of the right monitor, and a 50px dock on
+        // the left edge of the root window
+        let mut struts = Strut::parse_partial(&[0, 0, 30, 0, 0, 0, 0, 0, 1920, 3839, 0, 0], root);
+        struts.extend(Strut::parse(&[50, 0, 0, 0], root));
+
+        assert_eq!(
+            reserved_by_struts(&left, &struts),
+            Insets::new(0.0, 50.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            reserved_by_struts(&right, &struts),
+            Insets::new(30.0, 0.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Settings of libwmgr.
+//!
+//! Like the rest of libwmgr's state, settings are per thread, they apply to
+//! the [`apply_to_focused_window()`] calls made from the thread that set
+//! them, see [`set_config()`].
+//!
+//! [`apply_to_focused_window()`]: crate::apply_to_focused_window
+//! [`set_config()`]: crate::set_config
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::geometry::Insets;
+use crate::geometry::Size;
+
+/// Sizes that repeating a snap action on the same window cycles through, in
+/// fractions of the visible frame.
+///
+/// Every list is a full cycle.  An action starts from its own size, and
+/// each repeat moves to the next size in the list, wrapping around.  An
+/// action does not cycle if its own size is not in the list, so an empty
+/// list disables cycling for that family.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CycleSizes {
+    /// Width of `LeftHalf`, `RightHalf` and `CenterHalf`, height of
+    /// `TopHalf` and `BottomHalf`.
+    pub halves: Vec<f64>,
+    /// Width of `FirstThird`, `FirstTwoThirds` and the like, height of
+    /// `TopThird`, `TopTwoThirds` and the like.
+    pub thirds: Vec<f64>,
+    /// Width of the quarters, e.g., `TopLeftQuarter`.
+    pub corners: Vec<f64>,
+}
+
+impl Default for CycleSizes {
+    /// 1/2 → 2/3 → 1/3 for halves and corners, like Rectangle, 1/3 ↔ 2/3 for
+    /// thirds.
+    fn default() -> Self {
+        Self {
+            halves: vec![1.0 / 2.0, 2.0 / 3.0, 1.0 / 3.0],
+            thirds: vec![1.0 / 3.0, 2.0 / 3.0],
+            corners: vec![1.0 / 2.0, 2.0 / 3.0, 1.0 / 3.0],
+        }
+    }
+}
+
+/// Space around snapped windows, in pixels.
+///
+/// They apply to the actions that snap the window to a part of the screen,
+/// e.g., halves, thirds, quarters, sixths and `Maximize`.  `AlmostMaximize`
+/// only keeps the outer margins, as it has no neighbor.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Gaps {
+    /// Between two adjacent snapped windows, e.g., `LeftHalf` and
+    /// `RightHalf`.
+    pub inner: f64,
+    /// Between the windows and the edges of the visible frame.
+    pub outer: Insets,
+}
+
+/// Which displays a setting applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisplayMatch {
+    /// The display with this ID.
+    Id(DisplayId),
+    /// The displays whose frame has this size.
+    Resolution(Size),
+}
+
+impl DisplayMatch {
+    /// Whether `display` is one of them.
+    pub fn matches(&self, display: &Display) -> bool {
+        match self {
+            DisplayMatch::Id(id) => display.id == *id,
+            // Frames are not whole numbers with some scale factors
+            DisplayMatch::Resolution(size) => {
+                (display.frame.size.width - size.width).abs() < 0.5
+                    && (display.frame.size.height - size.height).abs() < 0.5
+            }
+        }
+    }
+}
+
+/// Space taken by something the window system does not know about, e.g., a
+/// custom status bar or a desktop widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReservedArea {
+    pub display: DisplayMatch,
+    /// Taken from the visible frame of the display.
+    pub insets: Insets,
+}
+
+/// All the settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Config {
+    pub cycle_sizes: CycleSizes,
+    /// Gaps of the displays that are not in `display_gaps`.
+    pub gaps: Gaps,
+    /// Gaps of specific displays.
+    pub display_gaps: BTreeMap<DisplayId, Gaps>,
+    /// Space reserved on top of what the window system reserves, no window
+    /// is put there by the actions.
+    pub reserved: Vec<ReservedArea>,
+}
+
+impl Config {
+    /// Return the gaps of this display.
+    pub fn gaps_of(&self, display: DisplayId) -> Gaps {
+        self.display_gaps
+            .get(&display)
+            .copied()
+            .unwrap_or(self.gaps)
+    }
+
+    /// Return the space reserved on this display by [`Config::reserved`]: the
+    /// first area matching the display by ID, or else the first one matching
+    /// its resolution.
+    pub fn reserved_of(&self, display: &Display) -> Insets {
+        self.reserved
+            .iter()
+            .find(|area| {
+                matches!(area.display, DisplayMatch::Id(_)) && area.display.matches(display)
+            })
+            .or_else(|| {
+                self.reserved
+                    .iter()
+                    .find(|area| area.display.matches(display))
+            })
+            .map(|area| area.insets)
+            .unwrap_or(Insets::ZERO)
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<Config> = RefCell::new(Config::default());
+}
+
+pub(crate) fn set(config: Config) {
+    CONFIG.with(|current| *current.borrow_mut() = config);
+}
+
+pub(crate) fn get() -> Config {
+    CONFIG.with(|current| current.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::geometry::Point;
+    use crate::geometry::Rect;
+
+    fn display(id: u64, width: f64, height: f64) -> Display {
+        Display {
+            id: DisplayId(id),
+            name: format!("Display {}", id),
+            frame: Rect::new(Point::new(0.0, 0.0), Size::new(width, height)),
+            reserved: Insets::ZERO,
+            scale_factor: 1.0,
+            rotation: 0,
+            is_primary: id == 0,
+        }
+    }
+
+    fn reserved(areas: &[(DisplayMatch, f64)]) -> Config {
+        Config {
+            reserved: areas
+                .iter()
+                .map(|&(display, top)| ReservedArea {
+                    display,
+                    insets: Insets::new(top, 0.0, 0.0, 0.0),
+                })
+                .collect(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn id_match_wins_over_resolution_match() {
+        let display = display(1, 1920.0, 1080.0);
+        let config = reserved(&[
+            (DisplayMatch::Resolution(Size::new(1920.0, 1080.0)), 10.0),
+            (DisplayMatch::Id(DisplayId(1)), 20.0),
+        ]);
+
+        // Although the resolution comes first
+        assert_eq!(config.reserved_of(&display).top, 20.0);
+        // The other displays of that resolution
+        assert_eq!(
+            config.reserved_of(&self::display(2, 1920.0, 1080.0)).top,
+            10.0
+        );
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let display = display(1, 1920.0, 1080.0);
+        let config = reserved(&[
+            (DisplayMatch::Resolution(Size::new(1920.0, 1080.0)), 10.0),
+            (DisplayMatch::Resolution(Size::new(1920.0, 1080.0)), 20.0),
+            (DisplayMatch::Id(DisplayId(1)), 30.0),
+            (DisplayMatch::Id(DisplayId(1)), 40.0),
+        ]);
+
+        assert_eq!(config.reserved_of(&display).top, 30.0);
+        assert_eq!(
+            config.reserved_of(&self::display(2, 1920.0, 1080.0)).top,
+            10.0
+        );
+    }
+
+    #[test]
+    fn resolution_match_tolerates_rounding() {
+        let config = reserved(&[(DisplayMatch::Resolution(Size::new(1920.0, 1080.0)), 10.0)]);
+
+        assert_eq!(config.reserved_of(&display(1, 1919.8, 1080.3)).top, 10.0);
+        assert_eq!(config.reserved_of(&display(1, 1919.0, 1080.0)).top, 0.0);
+    }
+
+    #[test]
+    fn no_match() {
+        let display = display(1, 1920.0, 1080.0);
+        let config = reserved(&[
+            (DisplayMatch::Id(DisplayId(2)), 10.0),
+            (DisplayMatch::Resolution(Size::new(2560.0, 1440.0)), 20.0),
+        ]);
+
+        assert_eq!(config.reserved_of(&display), Insets::ZERO);
+        assert_eq!(Config::default().reserved_of(&display), Insets::ZERO);
+    }
+}
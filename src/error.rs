@@ -1,4 +1,6 @@
+#[cfg(target_os = "macos")]
 use objc2_application_services::AXError;
+#[cfg(target_os = "macos")]
 use objc2_core_graphics::CGError;
 
 #[derive(Debug)]
@@ -6,6 +8,7 @@ pub enum Error {
     /// Cannot find the focused window.
     CannotFindFocusWindow,
     /// Error code from the macOS Accessibility APIs.
+    #[cfg(target_os = "macos")]
     AXError(AXError),
     /// Function should be in called from the main thread, but it is not.
     NotInMainThread,
@@ -17,6 +20,55 @@ pub enum Error {
     AlreadyInFirstDesktop,
     /// libwmgr can only handle 16 Workspaces at most.
     TooManyWorkspace,
+    /// The specified workspace does not exist.
+    NoSuchWorkspace,
+    /// The specified window does not exist, e.g., it has been closed.
+    NoSuchWindow,
     /// Error code from the macOS Core Graphics APIs.
+    #[cfg(target_os = "macos")]
     CGError(CGError),
+    /// I/O error, e.g., when talking to the window manager over a socket.
+    Io(std::io::Error),
+    /// The window manager refused a request, or replied with something we
+    /// cannot understand.
+    IpcError(String),
+    /// The window manager cannot do this, e.g., niri cannot set the frame of
+    /// a tiled window.
+    Unsupported(String),
+    /// Failed to connect to the X server.
+    #[cfg(feature = "x11")]
+    X11ConnectError(x11rb::errors::ConnectError),
+    /// Error from the X server, or the connection to it.
+    #[cfg(feature = "x11")]
+    X11Error(x11rb::errors::ReplyError),
+    /// Error from D-Bus, or the connection to it.
+    #[cfg(feature = "kwin")]
+    DBusError(zbus::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "x11")]
+impl From<x11rb::errors::ReplyError> for Error {
+    fn from(error: x11rb::errors::ReplyError) -> Self {
+        Error::X11Error(error)
+    }
+}
+
+#[cfg(feature = "x11")]
+impl From<x11rb::errors::ConnectionError> for Error {
+    fn from(error: x11rb::errors::ConnectionError) -> Self {
+        Error::X11Error(error.into())
+    }
+}
+
+#[cfg(feature = "kwin")]
+impl From<zbus::Error> for Error {
+    fn from(error: zbus::Error) -> Self {
+        Error::DBusError(error)
+    }
 }
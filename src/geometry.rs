@@ -0,0 +1,238 @@
+//! Geometry types used by libwmgr.
+//!
+//! Frames and points use the flipped coordinate system, i.e., the origin is
+//! the top-left corner of the main display and the y-axis grows downwards.
+
+#[cfg(target_os = "macos")]
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+
+/// A point.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub const ZERO: Point = Point { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Width and height.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Size {
+    pub const ZERO: Size = Size {
+        width: 0.0,
+        height: 0.0,
+    };
+
+    pub const fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+}
+
+/// Distances from the 4 edges of a rectangle, e.g., the space reserved by a
+/// menu bar or a dock.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Insets {
+    pub top: f64,
+    pub left: f64,
+    pub bottom: f64,
+    pub right: f64,
+}
+
+impl Insets {
+    pub const ZERO: Insets = Insets {
+        top: 0.0,
+        left: 0.0,
+        bottom: 0.0,
+        right: 0.0,
+    };
+
+    pub const fn new(top: f64, left: f64, bottom: f64, right: f64) -> Self {
+        Self {
+            top,
+            left,
+            bottom,
+            right,
+        }
+    }
+
+    /// Same insets on all the edges.
+    pub const fn uniform(value: f64) -> Self {
+        Self::new(value, value, value, value)
+    }
+
+    /// The insets that shrink `outer` to `inner`, 0 on the edges where `inner`
+    /// is not inside `outer`.
+    pub fn between(outer: &Rect, inner: &Rect) -> Self {
+        Self::new(
+            (inner.min().y - outer.min().y).max(0.0),
+            (inner.min().x - outer.min().x).max(0.0),
+            (outer.max().y - inner.max().y).max(0.0),
+            (outer.max().x - inner.max().x).max(0.0),
+        )
+    }
+}
+
+/// A rectangle, `origin` is its top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub origin: Point,
+    pub size: Size,
+}
+
+impl Rect {
+    pub const ZERO: Rect = Rect {
+        origin: Point::ZERO,
+        size: Size::ZERO,
+    };
+
+    pub const fn new(origin: Point, size: Size) -> Self {
+        Self { origin, size }
+    }
+
+    /// The smallest x and y coordinates.
+    pub fn min(&self) -> Point {
+        self.origin
+    }
+
+    /// The largest x and y coordinates.
+    pub fn max(&self) -> Point {
+        Point {
+            x: self.origin.x + self.size.width,
+            y: self.origin.y + self.size.height,
+        }
+    }
+
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.origin.x + self.size.width / 2.0,
+            y: self.origin.y + self.size.height / 2.0,
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.size.area()
+    }
+
+    /// Check if `point` is in this rect.
+    pub fn contains(&self, point: Point) -> bool {
+        let min = self.min();
+        let max = self.max();
+
+        // NOTE that when comparing with `max`, we use < rather than <=
+        let x_in_range = point.x >= min.x && point.x < max.x;
+        let y_in_range = point.y >= min.y && point.y < max.y;
+
+        x_in_range && y_in_range
+    }
+
+    /// Return the overlapping part of `self` and `other`, `None` if they do
+    /// not overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let min_x = self.min().x.max(other.min().x);
+        let min_y = self.min().y.max(other.min().y);
+        let max_x = self.max().x.min(other.max().x);
+        let max_y = self.max().y.min(other.max().y);
+
+        if min_x >= max_x || min_y >= max_y {
+            return None;
+        }
+
+        Some(Rect {
+            origin: Point { x: min_x, y: min_y },
+            size: Size {
+                width: max_x - min_x,
+                height: max_y - min_y,
+            },
+        })
+    }
+
+    /// Shrink this rect by `insets`.
+    pub fn inset(&self, insets: Insets) -> Rect {
+        Rect {
+            origin: Point {
+                x: self.origin.x + insets.left,
+                y: self.origin.y + insets.top,
+            },
+            size: Size {
+                width: self.size.width - insets.left - insets.right,
+                height: self.size.height - insets.top - insets.bottom,
+            },
+        }
+    }
+
+    /// Convert this rect between the flipped and unflipped coordinate systems.
+    ///
+    /// Core graphics APIs use flipped coordinate system, while AppKit uses the
+    /// unflippled version, they differ in the y-axis.  `main_screen_height` is
+    /// the height of the main screen, both systems put their origin on it.
+    pub fn flip_y(&self, main_screen_height: f64) -> Rect {
+        Rect {
+            origin: Point {
+                x: self.origin.x,
+                y: main_screen_height - (self.origin.y + self.size.height),
+            },
+            size: self.size,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<CGPoint> for Point {
+    fn from(point: CGPoint) -> Self {
+        Point::new(point.x, point.y)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<Point> for CGPoint {
+    fn from(point: Point) -> Self {
+        CGPoint::new(point.x, point.y)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<CGSize> for Size {
+    fn from(size: CGSize) -> Self {
+        Size::new(size.width, size.height)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<Size> for CGSize {
+    fn from(size: Size) -> Self {
+        CGSize::new(size.width, size.height)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<CGRect> for Rect {
+    fn from(rect: CGRect) -> Self {
+        Rect::new(rect.origin.into(), rect.size.into())
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<Rect> for CGRect {
+    fn from(rect: Rect) -> Self {
+        CGRect::new(rect.origin.into(), rect.size.into())
+    }
+}
@@ -0,0 +1,182 @@
+//! Frames of windows before libwmgr touched them, for [`Action::Restore`].
+//!
+//! Like Rectangle, a window's restore frame is the one it had before the
+//! first action.  Later actions keep it as long as the window stays where
+//! the last action put it, once the user moves or resizes the window by
+//! hand, the next action records a new restore frame.
+//!
+//! [`Action::Restore`]: crate::actions::Action::Restore
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use crate::backend::WindowId;
+use crate::geometry::Rect;
+
+/// At most this many windows are remembered, the least recently touched one
+/// is forgotten first.
+const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// The frame before the first action.
+    restore_frame: Rect,
+    /// The frame after the last action.
+    last_frame: Rect,
+    /// When this entry was last touched, bigger is more recent.
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct FrameHistory {
+    entries: BTreeMap<WindowId, Entry>,
+    clock: u64,
+}
+
+impl FrameHistory {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record that an action changed `window`'s frame from `before` to
+    /// `after`.
+    pub(crate) fn record(&mut self, window: WindowId, before: Rect, after: Rect) {
+        self.clock += 1;
+        let last_used = self.clock;
+
+        match self.entries.get_mut(&window) {
+            // Still where we put it, keep the restore frame
+            Some(entry) if entry.last_frame == before => {
+                entry.last_frame = after;
+                entry.last_used = last_used;
+            }
+            _ => {
+                if !self.entries.contains_key(&window) && self.entries.len() >= CAPACITY {
+                    self.evict_least_recently_used();
+                }
+                self.entries.insert(
+                    window,
+                    Entry {
+                        restore_frame: before,
+                        last_frame: after,
+                        last_used,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Forget `window` and return its restore frame.
+    pub(crate) fn take_restore_frame(&mut self, window: WindowId) -> Option<Rect> {
+        self.entries
+            .remove(&window)
+            .map(|entry| entry.restore_frame)
+    }
+
+    /// Forget the windows that are not in `alive`, i.e., the closed ones.
+    pub(crate) fn retain(&mut self, alive: &[WindowId]) {
+        self.entries.retain(|window, _| alive.contains(window));
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(window, _)| *window);
+        if let Some(window) = oldest {
+            self.entries.remove(&window);
+        }
+    }
+}
+
+thread_local! {
+    /// Like the macOS APIs, libwmgr is meant to be used from a single thread,
+    /// so every thread has its own history.
+    static HISTORY: RefCell<FrameHistory> = RefCell::new(FrameHistory::default());
+}
+
+/// Run `f` with the frame history of this thread.
+pub(crate) fn with_history<R>(f: impl FnOnce(&mut FrameHistory) -> R) -> R {
+    HISTORY.with(|history| f(&mut history.borrow_mut()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::geometry::Point;
+    use crate::geometry::Size;
+
+    const WINDOW: WindowId = WindowId(1);
+
+    fn rect(x: f64, y: f64) -> Rect {
+        Rect::new(Point::new(x, y), Size::new(400.0, 300.0))
+    }
+
+    #[test]
+    fn restore_to_before_the_first_action() {
+        let mut history = FrameHistory::default();
+        history.record(WINDOW, rect(0.0, 0.0), rect(1.0, 1.0));
+        history.record(WINDOW, rect(1.0, 1.0), rect(2.0, 2.0));
+
+        assert_eq!(history.take_restore_frame(WINDOW), Some(rect(0.0, 0.0)));
+        // Taken
+        assert_eq!(history.take_restore_frame(WINDOW), None);
+    }
+
+    #[test]
+    fn moved_by_hand_in_between() {
+        let mut history = FrameHistory::default();
+        history.record(WINDOW, rect(0.0, 0.0), rect(1.0, 1.0));
+        // Not where we left it
+        history.record(WINDOW, rect(5.0, 5.0), rect(2.0, 2.0));
+
+        assert_eq!(history.take_restore_frame(WINDOW), Some(rect(5.0, 5.0)));
+    }
+
+    #[test]
+    fn never_touched() {
+        let mut history = FrameHistory::default();
+        history.record(WINDOW, rect(0.0, 0.0), rect(1.0, 1.0));
+
+        assert_eq!(history.take_restore_frame(WindowId(2)), None);
+    }
+
+    #[test]
+    fn forget_closed_windows() {
+        let mut history = FrameHistory::default();
+        history.record(WindowId(1), rect(0.0, 0.0), rect(1.0, 1.0));
+        history.record(WindowId(2), rect(0.0, 0.0), rect(1.0, 1.0));
+
+        history.retain(&[WindowId(2)]);
+        assert_eq!(history.take_restore_frame(WindowId(1)), None);
+        assert_eq!(
+            history.take_restore_frame(WindowId(2)),
+            Some(rect(0.0, 0.0))
+        );
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn forget_the_least_recently_used() {
+        let mut history = FrameHistory::default();
+        for id in 0..CAPACITY as u64 {
+            history.record(WindowId(id), rect(0.0, 0.0), rect(1.0, 1.0));
+        }
+        // Window 0 gets used again, window 1 becomes the oldest
+        history.record(WindowId(0), rect(1.0, 1.0), rect(2.0, 2.0));
+        history.record(WindowId(CAPACITY as u64), rect(0.0, 0.0), rect(1.0, 1.0));
+
+        assert_eq!(history.entries.len(), CAPACITY);
+        assert_eq!(history.take_restore_frame(WindowId(1)), None);
+        assert_eq!(
+            history.take_restore_frame(WindowId(0)),
+            Some(rect(0.0, 0.0))
+        );
+        assert_eq!(
+            history.take_restore_frame(WindowId(CAPACITY as u64)),
+            Some(rect(0.0, 0.0))
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! The journal of operations done by [`apply_to_focused_window()`], for
+//! [`undo()`] and [`redo()`].
+//!
+//! [`apply_to_focused_window()`]: crate::apply_to_focused_window
+//! [`undo()`]: crate::undo
+//! [`redo()`]: crate::redo
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use crate::backend::WindowId;
+use crate::geometry::Rect;
+
+/// At most this many operations can be undone.
+const CAPACITY: usize = 100;
+
+/// What an operation did to a window.
+///
+/// Frames are absolute, so a move to another display is a frame change that
+/// needs nothing else to be reverted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Change {
+    /// The window was moved or resized.
+    Frame { before: Rect, after: Rect },
+    /// The window was moved between workspaces, in logical IDs, its frame
+    /// is left alone.
+    Workspace { from: usize, to: usize },
+}
+
+/// An operation done to a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct JournalEntry {
+    pub(crate) window: WindowId,
+    pub(crate) change: Change,
+}
+
+impl JournalEntry {
+    fn is_noop(&self) -> bool {
+        match self.change {
+            Change::Frame { before, after } => before == after,
+            Change::Workspace { from, to } => from == to,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Journal {
+    /// The most recent operation is at the back.
+    undo: VecDeque<JournalEntry>,
+    /// The most recently undone operation is at the back.
+    redo: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+
+    /// Record a new operation, which makes the undone operations impossible
+    /// to redo.
+    pub(crate) fn record(&mut self, entry: JournalEntry) {
+        if entry.is_noop() {
+            return;
+        }
+
+        self.redo.clear();
+        self.push(Direction::Undo, entry);
+    }
+
+    /// Take the next operation to undo or redo.
+    pub(crate) fn pop(&mut self, direction: Direction) -> Option<JournalEntry> {
+        match direction {
+            Direction::Undo => self.undo.pop_back(),
+            Direction::Redo => self.redo.pop(),
+        }
+    }
+
+    /// Put an operation onto the undo or redo stack.
+    pub(crate) fn push(&mut self, direction: Direction, entry: JournalEntry) {
+        match direction {
+            Direction::Undo => {
+                if self.undo.len() >= CAPACITY {
+                    self.undo.pop_front();
+                }
+                self.undo.push_back(entry);
+            }
+            Direction::Redo => self.redo.push(entry),
+        }
+    }
+
+    /// Forget the operations on the windows that are not in `alive`, i.e.,
+    /// the closed ones.
+    pub(crate) fn retain(&mut self, alive: &[WindowId]) {
+        self.undo.retain(|entry| alive.contains(&entry.window));
+        self.redo.retain(|entry| alive.contains(&entry.window));
+    }
+}
+
+thread_local! {
+    /// Like the frame history, every thread has its own journal.
+    static JOURNAL: RefCell<Journal> = RefCell::new(Journal::default());
+}
+
+/// Run `f` with the journal of this thread.
+pub(crate) fn with_journal<R>(f: impl FnOnce(&mut Journal) -> R) -> R {
+    JOURNAL.with(|journal| f(&mut journal.borrow_mut()))
+}
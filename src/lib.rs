@@ -1,737 +1,285 @@
-#![cfg(target_os = "macos")]
-
 pub mod actions;
-mod backend;
+pub mod backend;
+pub mod config;
 pub mod error;
+pub mod geometry;
+mod history;
+mod journal;
+pub mod plan;
+mod repeat;
+
+use std::time::Instant;
 
-use crate::backend::get_frontmost_window_origin;
-use crate::backend::get_next_workspace_logical_id;
-use crate::backend::get_previous_workspace_logical_id;
-use crate::backend::list_visible_frame_of_all_screens;
-use crate::backend::move_frontmost_window;
-use crate::backend::move_frontmost_window_to_workspace;
-use crate::backend::set_frontmost_window_frame;
 use actions::Action;
-use backend::get_active_screen_visible_frame;
-use backend::get_frontmost_window_size;
-use backend::toggle_fullscreen;
+use actions::ActionOptions;
+use backend::FrontmostWindow;
+use backend::WindowBackend;
+use backend::WindowId;
+use config::Config;
 use error::Error;
-use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use geometry::Rect;
+use history::with_history;
+use journal::with_journal;
+use journal::Change;
+use journal::Direction;
+use journal::JournalEntry;
+use plan::plan_with;
+use plan::Plan;
+use plan::WorkspaceTarget;
 
-/// Perform this action to the focused window.
+/// Perform this action to the focused window of `backend`, with these
+/// options.
 ///
-/// NOTE: this function should be called in the main thread, or it will error out.
-pub fn apply_to_focused_window(action: Action) -> Result<(), Error> {
-    let visible_frame = get_active_screen_visible_frame()?;
-
-    match action {
-        Action::TopHalf => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomHalf => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 2.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::LeftHalf => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::RightHalf => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 2.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::CenterHalf => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 4.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopLeftQuarter => {
-            let origin = visible_frame.origin;
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopRightQuarter => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 2.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomLeftQuarter => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 2.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomRightQuarter => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 2.0,
-                y: visible_frame.origin.y + visible_frame.size.height / 2.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 2.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopLeftSixth => {
-            let origin = visible_frame.origin;
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopCenterSixth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 3.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopRightSixth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width * 2.0 / 3.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomLeftSixth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 2.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomCenterSixth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 3.0,
-                y: visible_frame.origin.y + visible_frame.size.height / 2.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomRightSixth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width * 2.0 / 3.0,
-                y: visible_frame.origin.y + visible_frame.size.height / 2.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height / 2.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopThird => {
-            let origin = visible_frame.origin;
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 3.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::MiddleThird => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 3.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 3.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomThird => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height * 2.0 / 3.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 3.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::Center => {
-            let window_size = get_frontmost_window_size()?;
-            let origin = CGPoint {
-                x: visible_frame.origin.x + (visible_frame.size.width - window_size.width) / 2.0,
-                y: visible_frame.origin.y + (visible_frame.size.height - window_size.height) / 2.0,
-            };
-            move_frontmost_window(origin.x, origin.y)
-        }
-        Action::FirstFourth => {
-            let origin = visible_frame.origin;
-            let size = CGSize {
-                width: visible_frame.size.width / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::SecondFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 4.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::ThirdFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width * 2.0 / 4.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::LastFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width * 3.0 / 4.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::FirstThird => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::CenterThird => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 3.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::LastThird => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width * 2.0 / 3.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width / 3.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::FirstTwoThirds => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 2.0 / 3.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::CenterTwoThirds => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 6.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 2.0 / 3.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::LastTwoThirds => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 3.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 2.0 / 3.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::FirstThreeFourths => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 3.0 / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::CenterThreeFourths => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 8.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 3.0 / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::LastThreeFourths => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 4.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 3.0 / 4.0,
-                height: visible_frame.size.height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopThreeFourths => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height * 3.0 / 4.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomThreeFourths => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 4.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height * 3.0 / 4.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopTwoThirds => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height * 2.0 / 3.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::BottomTwoThirds => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 3.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height * 2.0 / 3.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-
-        Action::TopCenterTwoThirds => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x + visible_frame.size.width / 6.0,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width * 2.0 / 3.0,
-                height: visible_frame.size.height * 2.0 / 3.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopFirstFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 4.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopSecondFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height / 4.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 4.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopThirdFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height * 2.0 / 4.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 4.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::TopLastFourth => {
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: visible_frame.origin.y + visible_frame.size.height * 3.0 / 4.0,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: visible_frame.size.height / 4.0,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::MakeLarger => {
-            let window_origin = get_frontmost_window_origin()?;
-            let window_size = get_frontmost_window_size()?;
-            let delta_width = 20_f64;
-            let delta_height = window_size.height / window_size.width * delta_width;
-            let delta_origin_x = delta_width / 2.0;
-            let delta_origin_y = delta_height / 2.0;
+/// Frames set by actions are remembered per window (and per thread), so that
+/// [`Action::Restore`] can bring the window back to where it was before, and
+/// the operations are journaled for [`undo()`] and [`redo()`].
+///
+/// Doing the same snap action to the same window again cycles its size, see
+/// [`config::CycleSizes`], repeated nudges and resizes may accelerate within
+/// [`ActionOptions::repeat_timeout`], see [`ActionOptions::acceleration`].
+///
+/// NOTE: with [`backend::macos::MacOsBackend`], this function should be called
+/// in the main thread, or it will error out.
+pub fn apply_to_focused_window<B: WindowBackend + ?Sized>(
+    backend: &mut B,
+    action: Action,
+    options: &ActionOptions,
+) -> Result<(), Error> {
+    let window = backend.get_frontmost_window_id()?;
 
-            let new_width = {
-                let possible_value = window_size.width + delta_width;
-                if possible_value > visible_frame.size.width {
-                    visible_frame.size.width
-                } else {
-                    possible_value
-                }
-            };
-            let new_height = {
-                let possible_value = window_size.height + delta_height;
-                if possible_value > visible_frame.size.height {
-                    visible_frame.size.height
-                } else {
-                    possible_value
-                }
-            };
+    // Forget the closed windows, before their IDs get reused
+    if !with_history(|history| history.is_empty()) || !with_journal(|journal| journal.is_empty()) {
+        let alive = backend.list_window_ids()?;
+        with_history(|history| history.retain(&alive));
+        with_journal(|journal| journal.retain(&alive));
+    }
 
-            let new_origin_x = {
-                let possible_value = window_origin.x - delta_origin_x;
-                if possible_value < visible_frame.origin.x {
-                    visible_frame.origin.x
-                } else {
-                    possible_value
-                }
-            };
-            let new_origin_y = {
-                let possible_value = window_origin.y - delta_origin_y;
-                if possible_value < visible_frame.origin.y {
-                    visible_frame.origin.y
-                } else {
-                    possible_value
-                }
-            };
+    // Some backends cannot tell the frames of some windows, e.g., niri for
+    // its tiled windows, so do not ask for it unless the action needs it
+    if let Some(plan) = plan::plan_without_frame(action) {
+        repeat::reset();
+        return apply_plan_without_frame(backend, window, plan);
+    }
 
-            let origin = CGPoint {
-                x: new_origin_x,
-                y: new_origin_y,
-            };
-            let size = CGSize {
-                width: new_width,
-                height: new_height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::MakeSmaller => {
-            let window_origin = get_frontmost_window_origin()?;
-            let window_size = get_frontmost_window_size()?;
+    let FrontmostWindow {
+        screens: context,
+        frame: current_frame,
+        min_size,
+    } = backend.get_frontmost_window()?;
 
-            let delta_width = 20_f64;
-            let delta_height = window_size.height / window_size.width * delta_width;
+    let repeat = repeat::repeat_count(
+        window,
+        action,
+        current_frame,
+        Instant::now(),
+        options.repeat_timeout,
+    );
+    // Only a successful frame change continues the streak
+    repeat::reset();
 
-            let delta_origin_x = delta_width / 2.0;
-            let delta_origin_y = delta_height / 2.0;
+    match plan_with(
+        action,
+        &context,
+        current_frame,
+        min_size,
+        &config::get(),
+        options,
+        repeat,
+    ) {
+        Plan::Frame(frame) => {
+            backend.set_frontmost_window_frame(frame)?;
+            let after = record_frame_change(backend, window, current_frame)?;
+            repeat::record(window, action, after, repeat, Instant::now());
 
-            let origin = CGPoint {
-                x: window_origin.x + delta_origin_x,
-                y: window_origin.y + delta_origin_y,
-            };
-            let size = CGSize {
-                width: window_size.width - delta_width,
-                height: window_size.height - delta_height,
-            };
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
+            Ok(())
         }
-        Action::AlmostMaximize => {
-            let new_size = CGSize {
-                width: visible_frame.size.width * 0.8,
-                height: visible_frame.size.height * 0.8,
-            };
-            let new_origin = CGPoint {
-                x: visible_frame.origin.x + (visible_frame.size.width * 0.1),
-                y: visible_frame.origin.y + (visible_frame.size.height * 0.1),
-            };
-            let new_frame = CGRect {
-                origin: new_origin,
-                size: new_size,
-            };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::Maximize => {
-            let new_frame = CGRect {
-                origin: visible_frame.origin,
-                size: visible_frame.size,
-            };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::MaximizeWidth => {
-            let window_origin = get_frontmost_window_origin()?;
-            let window_size = get_frontmost_window_size()?;
-            let origin = CGPoint {
-                x: visible_frame.origin.x,
-                y: window_origin.y,
-            };
-            let size = CGSize {
-                width: visible_frame.size.width,
-                height: window_size.height,
-            };
+        Plan::MoveToDisplay { frame, .. } => {
+            backend.set_frontmost_window_frame(frame)?;
+            record_frame_change(backend, window, current_frame)?;
 
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
+            Ok(())
         }
-        Action::MaximizeHeight => {
-            let window_origin = get_frontmost_window_origin()?;
-            let window_size = get_frontmost_window_size()?;
-            let origin = CGPoint {
-                x: window_origin.x,
-                y: visible_frame.origin.y,
-            };
-            let size = CGSize {
-                width: window_size.width,
-                height: visible_frame.size.height,
-            };
+        Plan::Move(origin) => {
+            backend.move_frontmost_window(origin)?;
+            let after = record_frame_change(backend, window, current_frame)?;
+            repeat::record(window, action, after, repeat, Instant::now());
 
-            let new_frame = CGRect { origin, size };
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::MoveUp => {
-            let window_origin = get_frontmost_window_origin()?;
-            let new_y = (window_origin.y - 10.0).max(visible_frame.origin.y);
-            move_frontmost_window(window_origin.x, new_y)
-        }
-        Action::MoveDown => {
-            let window_origin = get_frontmost_window_origin()?;
-            let window_size = get_frontmost_window_size()?;
-            let new_y = (window_origin.y + 10.0)
-                .min(visible_frame.origin.y + visible_frame.size.height - window_size.height);
-            move_frontmost_window(window_origin.x, new_y)
-        }
-        Action::MoveLeft => {
-            let window_origin = get_frontmost_window_origin()?;
-            let new_x = (window_origin.x - 10.0).max(visible_frame.origin.x);
-            move_frontmost_window(new_x, window_origin.y)
-        }
-        Action::MoveRight => {
-            let window_origin = get_frontmost_window_origin()?;
-            let window_size = get_frontmost_window_size()?;
-            let new_x = (window_origin.x + 10.0)
-                .min(visible_frame.origin.x + visible_frame.size.width - window_size.width);
-            move_frontmost_window(new_x, window_origin.y)
+            Ok(())
         }
-        Action::NextDesktop => {
-            let Some(next_workspace_logical_id) = get_next_workspace_logical_id() else {
+        plan => apply_plan_without_frame(backend, window, plan),
+    }
+}
+
+/// Carry out `plan` on `window`, the focused window, for the plans that do
+/// not need its current frame.  The others do nothing.
+fn apply_plan_without_frame<B: WindowBackend + ?Sized>(
+    backend: &mut B,
+    window: WindowId,
+    plan: Plan,
+) -> Result<(), Error> {
+    match plan {
+        Plan::MoveToWorkspace(target) => {
+            let workspace_logical_id = match target {
+                WorkspaceTarget::Next => backend.get_next_workspace_logical_id()?,
+                WorkspaceTarget::Previous => backend.get_previous_workspace_logical_id()?,
+            };
+            let Some(workspace_logical_id) = workspace_logical_id else {
                 // nothing to do
                 return Ok(());
             };
 
-            move_frontmost_window_to_workspace(next_workspace_logical_id)
+            backend.move_frontmost_window_to_workspace(workspace_logical_id)?;
+
+            // Logical IDs are consecutive, see
+            // `WindowBackend::get_next_workspace_logical_id()`, so the window
+            // came from the one next to the target.
+            let from = match target {
+                WorkspaceTarget::Next => workspace_logical_id - 1,
+                WorkspaceTarget::Previous => workspace_logical_id + 1,
+            };
+            with_journal(|journal| {
+                journal.record(JournalEntry {
+                    window,
+                    change: Change::Workspace {
+                        from,
+                        to: workspace_logical_id,
+                    },
+                })
+            });
+
+            Ok(())
         }
-        Action::PreviousDesktop => {
-            let Some(previous_workspace_logical_id) = get_previous_workspace_logical_id() else {
-                // nothing to do
+        Plan::ToggleFullscreen => backend.toggle_fullscreen(),
+        Plan::Restore => {
+            let Some(frame) = with_history(|history| history.take_restore_frame(window)) else {
+                // never touched by us, nothing to restore
                 return Ok(());
             };
 
-            // Now let's switch the workspace
-            move_frontmost_window_to_workspace(previous_workspace_logical_id)
+            let current_frame = backend.get_frontmost_window_frame()?;
+            backend.set_frontmost_window_frame(frame)?;
+            journal_frame_change(backend, window, current_frame)?;
+
+            Ok(())
         }
-        Action::NextDisplay => {
-            const TOO_MANY_MONITORS: &str = "I don't think you can have so many monitors";
+        Plan::Frame(_) | Plan::Move(_) | Plan::MoveToDisplay { .. } | Plan::Noop => Ok(()),
+    }
+}
 
-            let frames = list_visible_frame_of_all_screens()?;
-            let n_frames = frames.len();
-            if n_frames == 0 {
-                return Err(Error::NoDisplay);
-            }
-            if n_frames == 1 {
-                return Ok(());
-            }
+/// Remember that `window` was at `before` prior to an action, for Restore and
+/// undo, return where it is now.
+fn record_frame_change<B: WindowBackend + ?Sized>(
+    backend: &B,
+    window: WindowId,
+    before: Rect,
+) -> Result<Rect, Error> {
+    let after = journal_frame_change(backend, window, before)?;
+    with_history(|history| history.record(window, before, after));
 
-            let index = frames
-                .iter()
-                .position(|fr| fr == &visible_frame)
-                .expect("active screen should be in the list");
-            let new_index: usize = {
-                let index_i32: i32 = index.try_into().expect(TOO_MANY_MONITORS);
-                let index_i32_plus_one = index_i32.checked_add(1).expect(TOO_MANY_MONITORS);
-                let final_value = index_i32_plus_one % n_frames as i32;
+    Ok(after)
+}
 
-                final_value
-                    .try_into()
-                    .expect("final value should be positive")
-            };
+/// Journal that `window` was moved from `before`, return where it is now.
+///
+/// The frame after the action is read back from the backend, since the window
+/// system may adjust it, e.g., to respect the window's minimum size.
+fn journal_frame_change<B: WindowBackend + ?Sized>(
+    backend: &B,
+    window: WindowId,
+    before: Rect,
+) -> Result<Rect, Error> {
+    let after = backend.get_frontmost_window_frame()?;
+    with_journal(|journal| {
+        journal.record(JournalEntry {
+            window,
+            change: Change::Frame { before, after },
+        })
+    });
 
-            let new_frame = frames[new_index];
+    Ok(after)
+}
 
-            set_frontmost_window_frame(new_frame)
-        }
-        Action::PreviousDisplay => {
-            const TOO_MANY_MONITORS: &str = "I don't think you can have so many monitors";
+/// Replace the settings used by [`apply_to_focused_window()`] in this thread.
+pub fn set_config(config: Config) {
+    config::set(config);
+}
 
-            let frames = list_visible_frame_of_all_screens()?;
-            let n_frames = frames.len();
-            if n_frames == 0 {
-                return Err(Error::NoDisplay);
-            }
-            if n_frames == 1 {
-                return Ok(());
-            }
-            let index = frames
-                .iter()
-                .position(|fr| fr == &visible_frame)
-                .expect("active screen should be in the list");
-            let new_index: usize = {
-                let index_i32: i32 = index.try_into().expect(TOO_MANY_MONITORS);
-                let index_i32_minus_one = index_i32 - 1;
-                let n_frames_i32: i32 = n_frames.try_into().expect(TOO_MANY_MONITORS);
-                let final_value = (index_i32_minus_one + n_frames_i32) % n_frames_i32;
+/// Return the settings used by [`apply_to_focused_window()`] in this thread.
+pub fn config() -> Config {
+    config::get()
+}
+
+/// Undo the last operation done by [`apply_to_focused_window()`] in this
+/// thread: move the window back to its previous frame and workspace.  The
+/// window gets focused.
+///
+/// Operations on the windows that have been closed are skipped.  Return
+/// `false` if there is nothing to undo.
+pub fn undo<B: WindowBackend + ?Sized>(backend: &mut B) -> Result<bool, Error> {
+    replay(backend, Direction::Undo)
+}
+
+/// Redo the last operation reverted by [`undo()`].
+///
+/// Like [`undo()`], operations on the closed windows are skipped, return
+/// `false` if there is nothing to redo.  Any new operation done by
+/// [`apply_to_focused_window()`] makes the undone operations impossible to
+/// redo.
+pub fn redo<B: WindowBackend + ?Sized>(backend: &mut B) -> Result<bool, Error> {
+    replay(backend, Direction::Redo)
+}
+
+fn replay<B: WindowBackend + ?Sized>(backend: &mut B, direction: Direction) -> Result<bool, Error> {
+    let alive = backend.list_window_ids()?;
+    let entry = loop {
+        let Some(entry) = with_journal(|journal| journal.pop(direction)) else {
+            return Ok(false);
+        };
+        if alive.contains(&entry.window) {
+            break entry;
+        }
+    };
 
-                final_value
-                    .try_into()
-                    .expect("final value should be positive")
+    match replay_entry(backend, &entry, direction) {
+        Ok(()) => {
+            let opposite = match direction {
+                Direction::Undo => Direction::Redo,
+                Direction::Redo => Direction::Undo,
             };
+            with_journal(|journal| journal.push(opposite, entry));
 
-            let new_frame = frames[new_index];
+            Ok(true)
+        }
+        Err(error) => {
+            // Keep it, so that it can be retried
+            with_journal(|journal| journal.push(direction, entry));
 
-            set_frontmost_window_frame(new_frame)
+            Err(error)
+        }
+    }
+}
+
+fn replay_entry<B: WindowBackend + ?Sized>(
+    backend: &mut B,
+    entry: &JournalEntry,
+    direction: Direction,
+) -> Result<(), Error> {
+    backend.focus_window(entry.window)?;
+    // Never touch another window if the focus did not move
+    if backend.get_frontmost_window_id()? != entry.window {
+        return Err(Error::CannotFindFocusWindow);
+    }
+
+    match (entry.change, direction) {
+        (Change::Frame { before, .. }, Direction::Undo) => {
+            backend.set_frontmost_window_frame(before)
+        }
+        (Change::Frame { after, .. }, Direction::Redo) => backend.set_frontmost_window_frame(after),
+        (Change::Workspace { from, .. }, Direction::Undo) => {
+            backend.move_frontmost_window_to_workspace(from)
         }
-        Action::Restore => {
-            todo!()
+        (Change::Workspace { to, .. }, Direction::Redo) => {
+            backend.move_frontmost_window_to_workspace(to)
         }
-        Action::ToggleFullscreen => toggle_fullscreen(),
     }
 }
@@ -0,0 +1,1566 @@
+//! Compute where an action would put the window, without touching any window.
+//!
+//! [`plan()`] is pure, it is what [`crate::apply_to_focused_window`] uses
+//! under the hood, launchers can also use it to preview the result of an
+//! action.
+
+use crate::actions::is_valid_grid;
+use crate::actions::Action;
+use crate::actions::ActionOptions;
+use crate::actions::DisplayMove;
+use crate::actions::Length;
+use crate::actions::Position;
+use crate::backend::Display;
+use crate::backend::DisplayId;
+use crate::config::Config;
+use crate::config::CycleSizes;
+use crate::geometry::Insets;
+use crate::geometry::Point;
+use crate::geometry::Rect;
+use crate::geometry::Size;
+
+/// Screen information needed to plan an action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenContext {
+    /// ID of the active display, i.e., the display the window is on.
+    pub active_display: DisplayId,
+    /// All the displays, the active one should be one of them.
+    pub displays: Vec<Display>,
+}
+
+impl ScreenContext {
+    /// Return the active display, `None` if it is not in `displays`.
+    pub fn active(&self) -> Option<&Display> {
+        self.displays
+            .iter()
+            .find(|display| display.id == self.active_display)
+    }
+}
+
+/// Which workspace to move the window to, relative to the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkspaceTarget {
+    Next,
+    Previous,
+}
+
+/// The outcome of an action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Plan {
+    /// Set the window frame to this rect.
+    Frame(Rect),
+    /// Move the window's origin to this point, keep its size.
+    Move(Point),
+    /// Move the window to another screen.
+    MoveToDisplay {
+        /// ID of the target display.
+        display: DisplayId,
+        /// The window frame on the target screen.
+        frame: Rect,
+    },
+    /// Move the window to another workspace.
+    MoveToWorkspace(WorkspaceTarget),
+    /// Toggle the window's fullscreen mode.
+    ToggleFullscreen,
+    /// Restore the window to its last position, which is not something we
+    /// can compute from the screens.
+    Restore,
+    /// Nothing to do.
+    Noop,
+}
+
+/// A fraction, `numerator / denominator`.
+///
+/// It is kept as a pair so that `length * numerator / denominator` gives the
+/// same result as writing the expression by hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fraction {
+    numerator: f64,
+    denominator: f64,
+}
+
+impl Fraction {
+    const fn new(numerator: f64, denominator: f64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Return this fraction of `length`.
+    fn of(self, length: f64) -> f64 {
+        length * self.numerator / self.denominator
+    }
+}
+
+/// A rectangular part of the visible frame, in fractions of its size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Region {
+    x: Fraction,
+    y: Fraction,
+    width: Fraction,
+    height: Fraction,
+}
+
+impl Region {
+    const fn new(x: Fraction, y: Fraction, width: Fraction, height: Fraction) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Convert this region to a rect in `visible_frame`.
+    fn to_rect(self, visible_frame: &Rect) -> Rect {
+        let origin = Point {
+            x: visible_frame.origin.x + self.x.of(visible_frame.size.width),
+            y: visible_frame.origin.y + self.y.of(visible_frame.size.height),
+        };
+        let size = Size {
+            width: self.width.of(visible_frame.size.width),
+            height: self.height.of(visible_frame.size.height),
+        };
+
+        Rect { origin, size }
+    }
+}
+
+/// A dimension of a region or a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Width,
+    Height,
+}
+
+/// Which side of the visible frame a region sticks to along an [`Axis`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    /// The left or top edge.
+    Start,
+    Center,
+    /// The right or bottom edge.
+    End,
+}
+
+/// Return the sizes that `action` cycles through, along with the dimension
+/// that cycles and where the region stays, `None` if `action` does not cycle.
+fn cycle_of(action: Action, sizes: &CycleSizes) -> Option<(&[f64], Axis, Anchor)> {
+    let cycle = match action {
+        Action::LeftHalf => (&sizes.halves, Axis::Width, Anchor::Start),
+        Action::CenterHalf => (&sizes.halves, Axis::Width, Anchor::Center),
+        Action::RightHalf => (&sizes.halves, Axis::Width, Anchor::End),
+        Action::TopHalf => (&sizes.halves, Axis::Height, Anchor::Start),
+        Action::BottomHalf => (&sizes.halves, Axis::Height, Anchor::End),
+
+        Action::FirstThird | Action::FirstTwoThirds => (&sizes.thirds, Axis::Width, Anchor::Start),
+        Action::CenterThird | Action::CenterTwoThirds => {
+            (&sizes.thirds, Axis::Width, Anchor::Center)
+        }
+        Action::LastThird | Action::LastTwoThirds => (&sizes.thirds, Axis::Width, Anchor::End),
+        Action::TopThird | Action::TopTwoThirds => (&sizes.thirds, Axis::Height, Anchor::Start),
+        Action::MiddleThird => (&sizes.thirds, Axis::Height, Anchor::Center),
+        Action::BottomThird | Action::BottomTwoThirds => (&sizes.thirds, Axis::Height, Anchor::End),
+
+        Action::TopLeftQuarter | Action::BottomLeftQuarter => {
+            (&sizes.corners, Axis::Width, Anchor::Start)
+        }
+        Action::TopRightQuarter | Action::BottomRightQuarter => {
+            (&sizes.corners, Axis::Width, Anchor::End)
+        }
+
+        _ => return None,
+    };
+
+    Some((cycle.0.as_slice(), cycle.1, cycle.2))
+}
+
+/// Return `rect`, where `action` puts the window in `visible_frame`, after
+/// `repeat` repeats of the action, `None` if it stays the same.
+fn cycle_rect(
+    action: Action,
+    rect: Rect,
+    visible_frame: &Rect,
+    sizes: &CycleSizes,
+    repeat: usize,
+) -> Option<Rect> {
+    let (cycle, axis, anchor) = cycle_of(action, sizes)?;
+    let (length, full_start, full_length) = match axis {
+        Axis::Width => (
+            rect.size.width,
+            visible_frame.origin.x,
+            visible_frame.size.width,
+        ),
+        Axis::Height => (
+            rect.size.height,
+            visible_frame.origin.y,
+            visible_frame.size.height,
+        ),
+    };
+
+    // Sizes come from the users, compare them loosely
+    let own_size = length / full_length;
+    let own_index = cycle
+        .iter()
+        .position(|size| (size - own_size).abs() < 1e-6)?;
+    let index = (own_index + repeat) % cycle.len();
+    if index == own_index {
+        return None;
+    }
+
+    let new_length = full_length * cycle[index];
+    let new_start = match anchor {
+        Anchor::Start => full_start,
+        Anchor::Center => full_start + (full_length - new_length) / 2.0,
+        Anchor::End => full_start + full_length - new_length,
+    };
+
+    let mut rect = rect;
+    match axis {
+        Axis::Width => {
+            rect.origin.x = new_start;
+            rect.size.width = new_length;
+        }
+        Axis::Height => {
+            rect.origin.y = new_start;
+            rect.size.height = new_length;
+        }
+    }
+
+    Some(rect)
+}
+
+/// Shrink `frame`, a part of `area`, by half of `inner` on the edges that are
+/// not on the edges of `area`, so that adjacent snapped windows end up `inner`
+/// apart.
+fn inset_inner_edges(frame: Rect, area: &Rect, inner: f64) -> Rect {
+    // Regions are computed in floating point, their edges may be a bit off
+    const EPSILON: f64 = 0.01;
+
+    if inner == 0.0 {
+        return frame;
+    }
+
+    let half = inner / 2.0;
+    let inset_if_inside = |edge: f64, area_edge: f64| {
+        if (edge - area_edge).abs() < EPSILON {
+            0.0
+        } else {
+            half
+        }
+    };
+    let insets = Insets {
+        top: inset_if_inside(frame.min().y, area.min().y),
+        left: inset_if_inside(frame.min().x, area.min().x),
+        bottom: inset_if_inside(frame.max().y, area.max().y),
+        right: inset_if_inside(frame.max().x, area.max().x),
+    };
+
+    frame.inset(insets)
+}
+
+/// Windows are assumed to be at least this large when the backend does not
+/// tell their minimum size.
+const FALLBACK_MIN_SIZE: Size = Size::new(100.0, 100.0);
+
+/// Return the minimum size of the window, from `min_size` reported by the
+/// backend.
+///
+/// Some windows report nonsense like 0x0, [`FALLBACK_MIN_SIZE`] is used for
+/// the sides that are not positive, like when it is not reported at all.
+fn effective_min_size(min_size: Option<Size>) -> Size {
+    let min_size = min_size.unwrap_or(FALLBACK_MIN_SIZE);
+    let or_fallback = |length: f64, fallback: f64| if length > 0.0 { length } else { fallback };
+
+    Size {
+        width: or_fallback(min_size.width, FALLBACK_MIN_SIZE.width),
+        height: or_fallback(min_size.height, FALLBACK_MIN_SIZE.height),
+    }
+}
+
+/// Return how much the height of a window of `size` changes when its width
+/// changes by `delta_width`, so that it keeps its aspect ratio.
+///
+/// A window without width has no aspect ratio, both sides change by the
+/// same amount then.
+fn height_delta(size: Size, delta_width: f64) -> f64 {
+    if size.width <= 0.0 {
+        delta_width
+    } else {
+        size.height / size.width * delta_width
+    }
+}
+
+/// One of the two edges of a window along an [`Axis`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    /// The left or top edge.
+    Start,
+    /// The right or bottom edge.
+    End,
+}
+
+/// Move `edge` of `span`, the `(start, end)` of a window along an axis, by
+/// `delta` pixels, outwards if it is positive, inwards otherwise.
+///
+/// The edge stays within `bounds`, the `(start, end)` of the visible frame,
+/// and the window does not get shorter than `min_length`, but what is already
+/// beyond them is left alone.
+fn move_edge(
+    span: (f64, f64),
+    bounds: (f64, f64),
+    min_length: f64,
+    delta: f64,
+    edge: Edge,
+) -> (f64, f64) {
+    let (start, end) = span;
+    let min_length = min_length.min(end - start);
+
+    match edge {
+        Edge::Start => {
+            let new_start = (start - delta)
+                .max(bounds.0.min(start))
+                .min(end - min_length);
+            (new_start, end)
+        }
+        Edge::End => {
+            let new_end = (end + delta).min(bounds.1.max(end)).max(start + min_length);
+            (start, new_end)
+        }
+    }
+}
+
+/// Return the part of `display` that the actions put windows in, i.e., its
+/// visible frame without the space reserved by `config`.
+fn work_area(display: &Display, config: &Config) -> Rect {
+    display.visible_frame().inset(config.reserved_of(display))
+}
+
+/// Return the indexes of `screens` from left to right, then top to bottom.
+fn spatial_order(screens: &[Rect]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..screens.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (screens[a].origin, screens[b].origin);
+        a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+    });
+
+    order
+}
+
+/// Return the index of the screen next to `screens[index]` in the direction of
+/// `action`, one of `DisplayLeft`, `DisplayRight`, `DisplayAbove` and
+/// `DisplayBelow`, `None` if there is none.
+///
+/// Among the screens that are in that direction, i.e., whose center is, the
+/// ones that face the current screen (overlap with it on the other axis) win,
+/// then the closest one.
+fn neighbor_screen(screens: &[Rect], index: usize, action: Action) -> Option<usize> {
+    let current = screens[index];
+    let center = current.center();
+
+    screens
+        .iter()
+        .enumerate()
+        .filter(|&(other_index, other)| {
+            let other_center = other.center();
+            other_index != index
+                && match action {
+                    Action::DisplayLeft => other_center.x < center.x,
+                    Action::DisplayRight => other_center.x > center.x,
+                    Action::DisplayAbove => other_center.y < center.y,
+                    _ => other_center.y > center.y,
+                }
+        })
+        .map(|(other_index, other)| {
+            let faces = match action {
+                Action::DisplayLeft | Action::DisplayRight => {
+                    other.min().y < current.max().y && current.min().y < other.max().y
+                }
+                _ => other.min().x < current.max().x && current.min().x < other.max().x,
+            };
+            let other_center = other.center();
+            let distance = (other_center.x - center.x).hypot(other_center.y - center.y);
+
+            (other_index, !faces, distance)
+        })
+        .min_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)))
+        .map(|(other_index, _, _)| other_index)
+}
+
+/// Map `frame` from the visible frame `from` to the visible frame `to`, keeping
+/// its position and size relative to them.
+fn scale_frame(frame: Rect, from: &Rect, to: &Rect) -> Rect {
+    let scale_x = to.size.width / from.size.width;
+    let scale_y = to.size.height / from.size.height;
+
+    Rect {
+        origin: Point {
+            x: to.origin.x + (frame.origin.x - from.origin.x) * scale_x,
+            y: to.origin.y + (frame.origin.y - from.origin.y) * scale_y,
+        },
+        size: Size {
+            width: frame.size.width * scale_x,
+            height: frame.size.height * scale_y,
+        },
+    }
+}
+
+/// Resolve `length` along an axis of the visible frame, which is
+/// `full_length` long.
+fn resolve_length(length: Length, full_length: f64) -> f64 {
+    match length {
+        Length::Fraction(fraction) => full_length * fraction,
+        Length::Pixels(pixels) => pixels,
+    }
+}
+
+/// Resolve where a window that is `window_length` long starts along an axis
+/// of the visible frame, which starts at `full_start` and is `full_length`
+/// long.
+fn resolve_position(
+    position: Position,
+    full_start: f64,
+    full_length: f64,
+    window_length: f64,
+) -> f64 {
+    match position {
+        Position::Fraction(fraction) => full_start + full_length * fraction,
+        Position::Pixels(pixels) => full_start + pixels,
+        Position::Start => full_start,
+        Position::Center => full_start + (full_length - window_length) / 2.0,
+        Position::End => full_start + full_length - window_length,
+    }
+}
+
+/// Return the region that a snap action fills, `None` if `action` does not
+/// snap the window to a fixed part of the screen, or is an invalid grid.
+fn snap_region(action: Action) -> Option<Region> {
+    const ZERO: Fraction = Fraction::new(0.0, 1.0);
+    const ONE: Fraction = Fraction::new(1.0, 1.0);
+    const HALF: Fraction = Fraction::new(1.0, 2.0);
+    const THIRD: Fraction = Fraction::new(1.0, 3.0);
+    const TWO_THIRDS: Fraction = Fraction::new(2.0, 3.0);
+    const FOURTH: Fraction = Fraction::new(1.0, 4.0);
+    const TWO_FOURTHS: Fraction = Fraction::new(2.0, 4.0);
+    const THREE_FOURTHS: Fraction = Fraction::new(3.0, 4.0);
+    const SIXTH: Fraction = Fraction::new(1.0, 6.0);
+    const EIGHTH: Fraction = Fraction::new(1.0, 8.0);
+    const TENTH: Fraction = Fraction::new(0.1, 1.0);
+    const EIGHT_TENTHS: Fraction = Fraction::new(0.8, 1.0);
+
+    let region = match action {
+        Action::TopHalf => Region::new(ZERO, ZERO, ONE, HALF),
+        Action::BottomHalf => Region::new(ZERO, HALF, ONE, HALF),
+        Action::LeftHalf => Region::new(ZERO, ZERO, HALF, ONE),
+        Action::RightHalf => Region::new(HALF, ZERO, HALF, ONE),
+        Action::CenterHalf => Region::new(FOURTH, ZERO, HALF, ONE),
+
+        Action::TopLeftQuarter => Region::new(ZERO, ZERO, HALF, HALF),
+        Action::TopRightQuarter => Region::new(HALF, ZERO, HALF, HALF),
+        Action::BottomLeftQuarter => Region::new(ZERO, HALF, HALF, HALF),
+        Action::BottomRightQuarter => Region::new(HALF, HALF, HALF, HALF),
+
+        Action::TopLeftSixth => Region::new(ZERO, ZERO, THIRD, HALF),
+        Action::TopCenterSixth => Region::new(THIRD, ZERO, THIRD, HALF),
+        Action::TopRightSixth => Region::new(TWO_THIRDS, ZERO, THIRD, HALF),
+        Action::BottomLeftSixth => Region::new(ZERO, HALF, THIRD, HALF),
+        Action::BottomCenterSixth => Region::new(THIRD, HALF, THIRD, HALF),
+        Action::BottomRightSixth => Region::new(TWO_THIRDS, HALF, THIRD, HALF),
+
+        Action::TopThird => Region::new(ZERO, ZERO, ONE, THIRD),
+        Action::MiddleThird => Region::new(ZERO, THIRD, ONE, THIRD),
+        Action::BottomThird => Region::new(ZERO, TWO_THIRDS, ONE, THIRD),
+
+        Action::FirstFourth => Region::new(ZERO, ZERO, FOURTH, ONE),
+        Action::SecondFourth => Region::new(FOURTH, ZERO, FOURTH, ONE),
+        Action::ThirdFourth => Region::new(TWO_FOURTHS, ZERO, FOURTH, ONE),
+        Action::LastFourth => Region::new(THREE_FOURTHS, ZERO, FOURTH, ONE),
+
+        Action::FirstThird => Region::new(ZERO, ZERO, THIRD, ONE),
+        Action::CenterThird => Region::new(THIRD, ZERO, THIRD, ONE),
+        Action::LastThird => Region::new(TWO_THIRDS, ZERO, THIRD, ONE),
+
+        Action::FirstTwoThirds => Region::new(ZERO, ZERO, TWO_THIRDS, ONE),
+        Action::CenterTwoThirds => Region::new(SIXTH, ZERO, TWO_THIRDS, ONE),
+        Action::LastTwoThirds => Region::new(THIRD, ZERO, TWO_THIRDS, ONE),
+
+        Action::FirstThreeFourths => Region::new(ZERO, ZERO, THREE_FOURTHS, ONE),
+        Action::CenterThreeFourths => Region::new(EIGHTH, ZERO, THREE_FOURTHS, ONE),
+        Action::LastThreeFourths => Region::new(FOURTH, ZERO, THREE_FOURTHS, ONE),
+
+        Action::TopThreeFourths => Region::new(ZERO, ZERO, ONE, THREE_FOURTHS),
+        Action::BottomThreeFourths => Region::new(ZERO, FOURTH, ONE, THREE_FOURTHS),
+
+        Action::TopTwoThirds => Region::new(ZERO, ZERO, ONE, TWO_THIRDS),
+        Action::BottomTwoThirds => Region::new(ZERO, THIRD, ONE, TWO_THIRDS),
+        Action::TopCenterTwoThirds => Region::new(SIXTH, ZERO, TWO_THIRDS, TWO_THIRDS),
+
+        Action::TopFirstFourth => Region::new(ZERO, ZERO, ONE, FOURTH),
+        Action::TopSecondFourth => Region::new(ZERO, FOURTH, ONE, FOURTH),
+        Action::TopThirdFourth => Region::new(ZERO, TWO_FOURTHS, ONE, FOURTH),
+        Action::TopLastFourth => Region::new(ZERO, THREE_FOURTHS, ONE, FOURTH),
+
+        Action::Grid {
+            columns,
+            rows,
+            x,
+            y,
+            width,
+            height,
+        } => {
+            if !is_valid_grid(columns, rows, x, y, width, height) {
+                return None;
+            }
+
+            let columns = f64::from(columns);
+            let rows = f64::from(rows);
+            Region::new(
+                Fraction::new(f64::from(x), columns),
+                Fraction::new(f64::from(y), rows),
+                Fraction::new(f64::from(width), columns),
+                Fraction::new(f64::from(height), rows),
+            )
+        }
+
+        Action::AlmostMaximize => Region::new(TENTH, TENTH, EIGHT_TENTHS, EIGHT_TENTHS),
+        Action::Maximize => Region::new(ZERO, ZERO, ONE, ONE),
+
+        Action::Center
+        | Action::Place { .. }
+        | Action::ResizeTo { .. }
+        | Action::MakeLarger
+        | Action::MakeSmaller
+        | Action::GrowLeft
+        | Action::GrowRight
+        | Action::GrowUp
+        | Action::GrowDown
+        | Action::ShrinkLeft
+        | Action::ShrinkRight
+        | Action::ShrinkUp
+        | Action::ShrinkDown
+        | Action::MaximizeWidth
+        | Action::MaximizeHeight
+        | Action::MoveUp
+        | Action::MoveDown
+        | Action::MoveLeft
+        | Action::MoveRight
+        | Action::MoveToTopEdge
+        | Action::MoveToBottomEdge
+        | Action::MoveToLeftEdge
+        | Action::MoveToRightEdge
+        | Action::NextDesktop
+        | Action::PreviousDesktop
+        | Action::NextDisplay
+        | Action::PreviousDisplay
+        | Action::DisplayLeft
+        | Action::DisplayRight
+        | Action::DisplayAbove
+        | Action::DisplayBelow
+        | Action::Restore
+        | Action::ToggleFullscreen => return None,
+    };
+
+    Some(region)
+}
+
+/// Return what `action` does if it cares about neither the screens nor the
+/// window's frame, `None` otherwise.
+pub(crate) fn plan_without_frame(action: Action) -> Option<Plan> {
+    match action {
+        Action::NextDesktop => Some(Plan::MoveToWorkspace(WorkspaceTarget::Next)),
+        Action::PreviousDesktop => Some(Plan::MoveToWorkspace(WorkspaceTarget::Previous)),
+        Action::Restore => Some(Plan::Restore),
+        Action::ToggleFullscreen => Some(Plan::ToggleFullscreen),
+        _ => None,
+    }
+}
+
+/// Compute what `action` would do to a window whose frame is `current_frame`,
+/// with the default settings and options, the first time it is done.
+///
+/// The actions that depend on the screens do nothing if the active display is
+/// not in `context.displays`.
+pub fn plan(action: Action, context: &ScreenContext, current_frame: Rect) -> Plan {
+    plan_with(
+        action,
+        context,
+        current_frame,
+        None,
+        &Config::default(),
+        &ActionOptions::default(),
+        0,
+    )
+}
+
+/// Like [`plan()`], with these settings and options, `min_size` being the
+/// window's minimum size if known, `repeat` being how many times in a row
+/// `action` has been done to the window right before, see [`CycleSizes`] and
+/// [`ActionOptions::acceleration`].
+pub fn plan_with(
+    action: Action,
+    context: &ScreenContext,
+    current_frame: Rect,
+    min_size: Option<Size>,
+    config: &Config,
+    options: &ActionOptions,
+    repeat: usize,
+) -> Plan {
+    if let Some(plan) = plan_without_frame(action) {
+        return plan;
+    }
+
+    let Some(active_display) = context.active() else {
+        return Plan::Noop;
+    };
+    let visible_frame = work_area(active_display, config);
+    let window_origin = current_frame.origin;
+    let window_size = current_frame.size;
+    let multiplier = options.acceleration.multiplier(repeat);
+    let nudge_x = options.nudge_step.pixels(visible_frame.size.width) * multiplier;
+    let nudge_y = options.nudge_step.pixels(visible_frame.size.height) * multiplier;
+
+    match action {
+        Action::Center => {
+            let origin = Point {
+                x: visible_frame.origin.x + (visible_frame.size.width - window_size.width) / 2.0,
+                y: visible_frame.origin.y + (visible_frame.size.height - window_size.height) / 2.0,
+            };
+            Plan::Move(origin)
+        }
+        Action::Place {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let size = Size {
+                width: resolve_length(width, visible_frame.size.width),
+                height: resolve_length(height, visible_frame.size.height),
+            };
+            let origin = Point {
+                x: resolve_position(
+                    x,
+                    visible_frame.origin.x,
+                    visible_frame.size.width,
+                    size.width,
+                ),
+                y: resolve_position(
+                    y,
+                    visible_frame.origin.y,
+                    visible_frame.size.height,
+                    size.height,
+                ),
+            };
+            Plan::Frame(Rect { origin, size })
+        }
+        Action::ResizeTo { width, height } => {
+            let size = Size {
+                width: resolve_length(width, visible_frame.size.width),
+                height: resolve_length(height, visible_frame.size.height),
+            };
+            let center = current_frame.center();
+            let origin = Point {
+                x: center.x - size.width / 2.0,
+                y: center.y - size.height / 2.0,
+            };
+            Plan::Frame(Rect { origin, size })
+        }
+        Action::MakeLarger => {
+            let delta_width = options.resize_step.pixels(visible_frame.size.width) * multiplier;
+            let delta_height = height_delta(window_size, delta_width);
+
+            // Up to the visible frame, never smaller than it already is
+            let new_width = (window_size.width + delta_width)
+                .min(visible_frame.size.width.max(window_size.width));
+            let new_height = (window_size.height + delta_height)
+                .min(visible_frame.size.height.max(window_size.height));
+
+            // Grow around the center, then push the window back onto the
+            // screen if it crossed an edge
+            let new_origin_x = {
+                let possible_value = window_origin.x - (new_width - window_size.width) / 2.0;
+                possible_value
+                    .min(visible_frame.max().x - new_width)
+                    .max(visible_frame.min().x)
+            };
+            let new_origin_y = {
+                let possible_value = window_origin.y - (new_height - window_size.height) / 2.0;
+                possible_value
+                    .min(visible_frame.max().y - new_height)
+                    .max(visible_frame.min().y)
+            };
+
+            let origin = Point {
+                x: new_origin_x,
+                y: new_origin_y,
+            };
+            let size = Size {
+                width: new_width,
+                height: new_height,
+            };
+            Plan::Frame(Rect { origin, size })
+        }
+        Action::MakeSmaller => {
+            let delta_width = options.resize_step.pixels(visible_frame.size.width) * multiplier;
+            let delta_height = height_delta(window_size, delta_width);
+
+            // Down to the minimum size, never larger than it already is
+            let min_size = effective_min_size(min_size);
+            let new_width =
+                (window_size.width - delta_width).max(min_size.width.min(window_size.width));
+            let new_height =
+                (window_size.height - delta_height).max(min_size.height.min(window_size.height));
+
+            let origin = Point {
+                x: window_origin.x + (window_size.width - new_width) / 2.0,
+                y: window_origin.y + (window_size.height - new_height) / 2.0,
+            };
+            let size = Size {
+                width: new_width,
+                height: new_height,
+            };
+            Plan::Frame(Rect { origin, size })
+        }
+        Action::GrowLeft
+        | Action::GrowRight
+        | Action::GrowUp
+        | Action::GrowDown
+        | Action::ShrinkLeft
+        | Action::ShrinkRight
+        | Action::ShrinkUp
+        | Action::ShrinkDown => {
+            let (axis, edge, grow) = match action {
+                Action::GrowLeft => (Axis::Width, Edge::Start, true),
+                Action::GrowRight => (Axis::Width, Edge::End, true),
+                Action::GrowUp => (Axis::Height, Edge::Start, true),
+                Action::GrowDown => (Axis::Height, Edge::End, true),
+                Action::ShrinkLeft => (Axis::Width, Edge::Start, false),
+                Action::ShrinkRight => (Axis::Width, Edge::End, false),
+                Action::ShrinkUp => (Axis::Height, Edge::Start, false),
+                _ => (Axis::Height, Edge::End, false),
+            };
+            let min_size = effective_min_size(min_size);
+            let (span, bounds, min_length, screen_length) = match axis {
+                Axis::Width => (
+                    (current_frame.min().x, current_frame.max().x),
+                    (visible_frame.min().x, visible_frame.max().x),
+                    min_size.width,
+                    visible_frame.size.width,
+                ),
+                Axis::Height => (
+                    (current_frame.min().y, current_frame.max().y),
+                    (visible_frame.min().y, visible_frame.max().y),
+                    min_size.height,
+                    visible_frame.size.height,
+                ),
+            };
+            let step = options.resize_step.pixels(screen_length) * multiplier;
+            let delta = if grow { step } else { -step };
+
+            let (start, end) = move_edge(span, bounds, min_length, delta, edge);
+            let mut frame = current_frame;
+            match axis {
+                Axis::Width => {
+                    frame.origin.x = start;
+                    frame.size.width = end - start;
+                }
+                Axis::Height => {
+                    frame.origin.y = start;
+                    frame.size.height = end - start;
+                }
+            }
+            Plan::Frame(frame)
+        }
+        Action::MaximizeWidth => {
+            let origin = Point {
+                x: visible_frame.origin.x,
+                y: window_origin.y,
+            };
+            let size = Size {
+                width: visible_frame.size.width,
+                height: window_size.height,
+            };
+            Plan::Frame(Rect { origin, size })
+        }
+        Action::MaximizeHeight => {
+            let origin = Point {
+                x: window_origin.x,
+                y: visible_frame.origin.y,
+            };
+            let size = Size {
+                width: window_size.width,
+                height: visible_frame.size.height,
+            };
+            Plan::Frame(Rect { origin, size })
+        }
+        Action::MoveUp => {
+            let new_y = (window_origin.y - nudge_y).max(visible_frame.origin.y);
+            Plan::Move(Point::new(window_origin.x, new_y))
+        }
+        Action::MoveDown => {
+            let new_y = (window_origin.y + nudge_y)
+                .min(visible_frame.origin.y + visible_frame.size.height - window_size.height);
+            Plan::Move(Point::new(window_origin.x, new_y))
+        }
+        Action::MoveLeft => {
+            let new_x = (window_origin.x - nudge_x).max(visible_frame.origin.x);
+            Plan::Move(Point::new(new_x, window_origin.y))
+        }
+        Action::MoveRight => {
+            let new_x = (window_origin.x + nudge_x)
+                .min(visible_frame.origin.x + visible_frame.size.width - window_size.width);
+            Plan::Move(Point::new(new_x, window_origin.y))
+        }
+        Action::MoveToTopEdge => Plan::Move(Point::new(window_origin.x, visible_frame.min().y)),
+        Action::MoveToBottomEdge => Plan::Move(Point::new(
+            window_origin.x,
+            visible_frame.max().y - window_size.height,
+        )),
+        Action::MoveToLeftEdge => Plan::Move(Point::new(visible_frame.min().x, window_origin.y)),
+        Action::MoveToRightEdge => Plan::Move(Point::new(
+            visible_frame.max().x - window_size.width,
+            window_origin.y,
+        )),
+        Action::NextDisplay
+        | Action::PreviousDisplay
+        | Action::DisplayLeft
+        | Action::DisplayRight
+        | Action::DisplayAbove
+        | Action::DisplayBelow => {
+            let n_displays = context.displays.len();
+            if n_displays <= 1 {
+                return Plan::Noop;
+            }
+
+            let frames: Vec<Rect> = context
+                .displays
+                .iter()
+                .map(|display| display.frame)
+                .collect();
+            let Some(index) = context
+                .displays
+                .iter()
+                .position(|display| display.id == active_display.id)
+            else {
+                return Plan::Noop;
+            };
+            let new_index = match action {
+                Action::NextDisplay | Action::PreviousDisplay => {
+                    let order = spatial_order(&frames);
+                    let Some(position) = order.iter().position(|&other| other == index) else {
+                        return Plan::Noop;
+                    };
+                    let new_position = if action == Action::NextDisplay {
+                        (position + 1) % n_displays
+                    } else {
+                        (position + n_displays - 1) % n_displays
+                    };
+                    order[new_position]
+                }
+                _ => {
+                    let Some(neighbor) = neighbor_screen(&frames, index, action) else {
+                        // nothing over there
+                        return Plan::Noop;
+                    };
+                    neighbor
+                }
+            };
+
+            let target = &context.displays[new_index];
+            let frame = match options.display_move {
+                DisplayMove::Scale => {
+                    scale_frame(current_frame, &visible_frame, &work_area(target, config))
+                }
+                DisplayMove::Maximize => work_area(target, config),
+            };
+
+            Plan::MoveToDisplay {
+                display: target.id,
+                frame,
+            }
+        }
+        _ => {
+            let gaps = config.gaps_of(active_display.id);
+            let area = visible_frame.inset(gaps.outer);
+
+            // Non-snap actions are handled above, this is an invalid grid
+            let Some(region) = snap_region(action) else {
+                return Plan::Noop;
+            };
+            let mut frame = region.to_rect(&area);
+            if repeat > 0 {
+                if let Some(cycled) = cycle_rect(action, frame, &area, &config.cycle_sizes, repeat)
+                {
+                    frame = cycled;
+                }
+            }
+            // It has no neighbor to keep apart from
+            if action != Action::AlmostMaximize {
+                frame = inset_inner_edges(frame, &area, gaps.inner);
+            }
+            Plan::Frame(frame)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::actions::Step;
+    use crate::config::DisplayMatch;
+    use crate::config::Gaps;
+    use crate::config::ReservedArea;
+
+    type Baseline = fn(f64, f64, f64, f64) -> [f64; 4];
+
+    /// Where the snap actions put a window in the visible frame `(x, y, w,
+    /// h)`, computed the way the original implementation did, as `[x, y,
+    /// width, height]`.
+    const SNAPS: &[(Action, Baseline)] = &[
+        // halves
+        (Action::TopHalf, |x, y, w, h| [x, y, w, h / 2.0]),
+        (Action::BottomHalf, |x, y, w, h| {
+            [x, y + h / 2.0, w, h / 2.0]
+        }),
+        (Action::LeftHalf, |x, y, w, h| [x, y, w / 2.0, h]),
+        (Action::RightHalf, |x, y, w, h| [x + w / 2.0, y, w / 2.0, h]),
+        (Action::CenterHalf, |x, y, w, h| {
+            [x + w / 4.0, y, w / 2.0, h]
+        }),
+        // thirds
+        (Action::TopThird, |x, y, w, h| [x, y, w, h / 3.0]),
+        (Action::MiddleThird, |x, y, w, h| {
+            [x, y + h / 3.0, w, h / 3.0]
+        }),
+        (Action::BottomThird, |x, y, w, h| {
+            [x, y + h * 2.0 / 3.0, w, h / 3.0]
+        }),
+        (Action::FirstThird, |x, y, w, h| [x, y, w / 3.0, h]),
+        (Action::CenterThird, |x, y, w, h| {
+            [x + w / 3.0, y, w / 3.0, h]
+        }),
+        (Action::LastThird, |x, y, w, h| {
+            [x + w * 2.0 / 3.0, y, w / 3.0, h]
+        }),
+        (Action::FirstTwoThirds, |x, y, w, h| {
+            [x, y, w * 2.0 / 3.0, h]
+        }),
+        (Action::CenterTwoThirds, |x, y, w, h| {
+            [x + w / 6.0, y, w * 2.0 / 3.0, h]
+        }),
+        (Action::LastTwoThirds, |x, y, w, h| {
+            [x + w / 3.0, y, w * 2.0 / 3.0, h]
+        }),
+        (Action::TopTwoThirds, |x, y, w, h| [x, y, w, h * 2.0 / 3.0]),
+        (Action::BottomTwoThirds, |x, y, w, h| {
+            [x, y + h / 3.0, w, h * 2.0 / 3.0]
+        }),
+        (Action::TopCenterTwoThirds, |x, y, w, h| {
+            [x + w / 6.0, y, w * 2.0 / 3.0, h * 2.0 / 3.0]
+        }),
+        // fourths
+        (Action::FirstFourth, |x, y, w, h| [x, y, w / 4.0, h]),
+        (Action::SecondFourth, |x, y, w, h| {
+            [x + w / 4.0, y, w / 4.0, h]
+        }),
+        (Action::ThirdFourth, |x, y, w, h| {
+            [x + w * 2.0 / 4.0, y, w / 4.0, h]
+        }),
+        (Action::LastFourth, |x, y, w, h| {
+            [x + w * 3.0 / 4.0, y, w / 4.0, h]
+        }),
+        (Action::TopFirstFourth, |x, y, w, h| [x, y, w, h / 4.0]),
+        (Action::TopSecondFourth, |x, y, w, h| {
+            [x, y + h / 4.0, w, h / 4.0]
+        }),
+        (Action::TopThirdFourth, |x, y, w, h| {
+            [x, y + h * 2.0 / 4.0, w, h / 4.0]
+        }),
+        (Action::TopLastFourth, |x, y, w, h| {
+            [x, y + h * 3.0 / 4.0, w, h / 4.0]
+        }),
+        (Action::FirstThreeFourths, |x, y, w, h| {
+            [x, y, w * 3.0 / 4.0, h]
+        }),
+        (Action::CenterThreeFourths, |x, y, w, h| {
+            [x + w / 8.0, y, w * 3.0 / 4.0, h]
+        }),
+        (Action::LastThreeFourths, |x, y, w, h| {
+            [x + w / 4.0, y, w * 3.0 / 4.0, h]
+        }),
+        (Action::TopThreeFourths, |x, y, w, h| {
+            [x, y, w, h * 3.0 / 4.0]
+        }),
+        (Action::BottomThreeFourths, |x, y, w, h| {
+            [x, y + h / 4.0, w, h * 3.0 / 4.0]
+        }),
+        // quarters
+        (Action::TopLeftQuarter, |x, y, w, h| {
+            [x, y, w / 2.0, h / 2.0]
+        }),
+        (Action::TopRightQuarter, |x, y, w, h| {
+            [x + w / 2.0, y, w / 2.0, h / 2.0]
+        }),
+        (Action::BottomLeftQuarter, |x, y, w, h| {
+            [x, y + h / 2.0, w / 2.0, h / 2.0]
+        }),
+        (Action::BottomRightQuarter, |x, y, w, h| {
+            [x + w / 2.0, y + h / 2.0, w / 2.0, h / 2.0]
+        }),
+        // sixths
+        (Action::TopLeftSixth, |x, y, w, h| [x, y, w / 3.0, h / 2.0]),
+        (Action::TopCenterSixth, |x, y, w, h| {
+            [x + w / 3.0, y, w / 3.0, h / 2.0]
+        }),
+        (Action::TopRightSixth, |x, y, w, h| {
+            [x + w * 2.0 / 3.0, y, w / 3.0, h / 2.0]
+        }),
+        (Action::BottomLeftSixth, |x, y, w, h| {
+            [x, y + h / 2.0, w / 3.0, h / 2.0]
+        }),
+        (Action::BottomCenterSixth, |x, y, w, h| {
+            [x + w / 3.0, y + h / 2.0, w / 3.0, h / 2.0]
+        }),
+        (Action::BottomRightSixth, |x, y, w, h| {
+            [x + w * 2.0 / 3.0, y + h / 2.0, w / 3.0, h / 2.0]
+        }),
+        // whole
+        (Action::Maximize, |x, y, w, h| [x, y, w, h]),
+    ];
+
+    /// Screens as `(frame, reserved)`: a regular one, odd-sized ones, and
+    /// ones not starting at the origin, including negative coordinates.
+    const SCREENS: &[([f64; 4], Insets)] = &[
+        ([0.0, 0.0, 1440.0, 900.0], Insets::new(25.0, 0.0, 0.0, 0.0)),
+        ([0.0, 0.0, 1367.0, 769.0], Insets::ZERO),
+        ([0.0, 0.0, 1001.0, 777.0], Insets::new(37.0, 0.0, 61.0, 3.0)),
+        ([1440.0, 0.0, 1920.0, 1080.0], Insets::ZERO),
+        (
+            [-1921.0, 312.0, 1919.0, 1081.0],
+            Insets::new(0.0, 47.0, 0.0, 0.0),
+        ),
+        (
+            [-333.0, -1083.0, 1201.0, 1083.0],
+            Insets::new(29.0, 0.0, 0.0, 0.0),
+        ),
+    ];
+
+    fn rect([x, y, width, height]: [f64; 4]) -> Rect {
+        Rect::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    /// A context with one display per screen, the active one is `active`.
+    fn context(active: usize) -> ScreenContext {
+        let displays = SCREENS
+            .iter()
+            .enumerate()
+            .map(|(index, (frame, reserved))| Display {
+                id: DisplayId(index as u64),
+                name: format!("Screen {}", index),
+                frame: rect(*frame),
+                reserved: *reserved,
+                scale_factor: 1.0,
+                rotation: 0,
+                is_primary: index == 0,
+            })
+            .collect();
+
+        ScreenContext {
+            active_display: DisplayId(active as u64),
+            displays,
+        }
+    }
+
+    #[test]
+    fn snaps_match_the_baseline() {
+        let window = rect([10.0, 20.0, 300.0, 200.0]);
+
+        for index in 0..SCREENS.len() {
+            let context = context(index);
+            let visible_frame = context.active().unwrap().visible_frame();
+            let Rect {
+                origin: Point { x, y },
+                size: Size { width, height },
+            } = visible_frame;
+
+            for (action, baseline) in SNAPS {
+                assert_eq!(
+                    plan(*action, &context, window),
+                    Plan::Frame(rect(baseline(x, y, width, height))),
+                    "{:?} on {:?}",
+                    action,
+                    visible_frame
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn center_matches_the_baseline() {
+        for index in 0..SCREENS.len() {
+            let context = context(index);
+            let Rect { origin, size } = context.active().unwrap().visible_frame();
+            let window = rect([5.0, 7.0, 301.0, 199.0]);
+
+            assert_eq!(
+                plan(Action::Center, &context, window),
+                Plan::Move(Point::new(
+                    origin.x + (size.width - window.size.width) / 2.0,
+                    origin.y + (size.height - window.size.height) / 2.0,
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn odd_sized_screen() {
+        // 1367x769, spelled out to check the table itself
+        let context = context(1);
+        let window = rect([0.0, 0.0, 100.0, 100.0]);
+
+        assert_eq!(
+            plan(Action::RightHalf, &context, window),
+            Plan::Frame(rect([683.5, 0.0, 683.5, 769.0]))
+        );
+        assert_eq!(
+            plan(Action::BottomRightQuarter, &context, window),
+            Plan::Frame(rect([683.5, 384.5, 683.5, 384.5]))
+        );
+        assert_eq!(
+            plan(Action::LastFourth, &context, window),
+            Plan::Frame(rect([1025.25, 0.0, 341.75, 769.0]))
+        );
+    }
+
+    #[test]
+    fn screen_not_at_the_origin() {
+        // (-1874, 312) 1872x1081 once the 47px on the left is reserved
+        let context = context(4);
+        let window = rect([-1000.0, 400.0, 100.0, 100.0]);
+
+        assert_eq!(
+            plan(Action::LeftHalf, &context, window),
+            Plan::Frame(rect([-1874.0, 312.0, 936.0, 1081.0]))
+        );
+        assert_eq!(
+            plan(Action::BottomHalf, &context, window),
+            Plan::Frame(rect([-1874.0, 852.5, 1872.0, 540.5]))
+        );
+        assert_eq!(
+            plan(Action::Maximize, &context, window),
+            Plan::Frame(rect([-1874.0, 312.0, 1872.0, 1081.0]))
+        );
+    }
+
+    #[test]
+    fn resize_a_window_without_width() {
+        let context = context(1);
+        let window = rect([100.0, 100.0, 0.0, 50.0]);
+
+        // Both sides grow by the 20px step, around the center
+        assert_eq!(
+            plan(Action::MakeLarger, &context, window),
+            Plan::Frame(rect([90.0, 90.0, 20.0, 70.0]))
+        );
+        // Already below the minimum size
+        assert_eq!(
+            plan(Action::MakeSmaller, &context, window),
+            Plan::Frame(window)
+        );
+    }
+
+    #[test]
+    fn make_smaller_ignores_a_zero_min_size() {
+        let context = context(1);
+        let window = rect([100.0, 100.0, 110.0, 110.0]);
+        let plan_smaller = |min_size| {
+            plan_with(
+                Action::MakeSmaller,
+                &context,
+                window,
+                min_size,
+                &Config::default(),
+                &ActionOptions::default(),
+                0,
+            )
+        };
+
+        assert_eq!(
+            plan_smaller(Some(Size::ZERO)),
+            Plan::Frame(rect([105.0, 105.0, 100.0, 100.0]))
+        );
+        // Real ones are respected
+        assert_eq!(
+            plan_smaller(Some(Size::new(105.0, 0.0))),
+            Plan::Frame(rect([102.5, 105.0, 105.0, 100.0]))
+        );
+    }
+
+    #[test]
+    fn shrink_below_the_fallback_min_size() {
+        let context = context(1);
+        let window = rect([100.0, 100.0, 50.0, 50.0]);
+        let plan_with_min_size = |action, min_size| {
+            plan_with(
+                action,
+                &context,
+                window,
+                min_size,
+                &Config::default(),
+                &ActionOptions::default(),
+                0,
+            )
+        };
+
+        // Reported by the backend
+        assert_eq!(
+            plan_with_min_size(Action::MakeSmaller, Some(Size::new(40.0, 40.0))),
+            Plan::Frame(rect([105.0, 105.0, 40.0, 40.0]))
+        );
+        assert_eq!(
+            plan_with_min_size(Action::ShrinkRight, Some(Size::new(40.0, 40.0))),
+            Plan::Frame(rect([100.0, 100.0, 40.0, 50.0]))
+        );
+        // Unknown, already smaller than the fallback, so it stays
+        assert_eq!(
+            plan_with_min_size(Action::MakeSmaller, None),
+            Plan::Frame(window)
+        );
+    }
+
+    /// Unevenly stacked screens, in no particular order: the main one, a
+    /// taller one to its right and lower, one to its left and higher, and one
+    /// above it, shifted to the right.
+    const STACKED: &[[f64; 4]] = &[
+        [0.0, 0.0, 1440.0, 900.0],
+        [1440.0, 300.0, 1920.0, 1080.0],
+        [-1280.0, -400.0, 1280.0, 1024.0],
+        [200.0, -1080.0, 1920.0, 1080.0],
+    ];
+
+    #[test]
+    fn neighbor_screens() {
+        let screens: Vec<Rect> = STACKED.iter().copied().map(rect).collect();
+
+        assert_eq!(neighbor_screen(&screens, 0, Action::DisplayLeft), Some(2));
+        // The one above is closer, but does not face the main one
+        assert_eq!(neighbor_screen(&screens, 0, Action::DisplayRight), Some(1));
+        // The one on the left is higher, but does not face the main one
+        assert_eq!(neighbor_screen(&screens, 0, Action::DisplayAbove), Some(3));
+        // None faces the main one, the closest one lower wins
+        assert_eq!(neighbor_screen(&screens, 0, Action::DisplayBelow), Some(1));
+        assert_eq!(neighbor_screen(&screens, 1, Action::DisplayLeft), Some(0));
+        assert_eq!(neighbor_screen(&screens, 3, Action::DisplayBelow), Some(0));
+    }
+
+    #[test]
+    fn no_neighbor_screen() {
+        let screens: Vec<Rect> = STACKED.iter().copied().map(rect).collect();
+
+        assert_eq!(neighbor_screen(&screens, 2, Action::DisplayLeft), None);
+        assert_eq!(neighbor_screen(&screens, 1, Action::DisplayRight), None);
+        assert_eq!(neighbor_screen(&screens, 3, Action::DisplayAbove), None);
+        assert_eq!(neighbor_screen(&screens, 1, Action::DisplayBelow), None);
+        assert_eq!(
+            neighbor_screen(&screens[..1], 0, Action::DisplayRight),
+            None
+        );
+    }
+
+    #[test]
+    fn next_and_previous_display_in_spatial_order() {
+        let screens: Vec<Rect> = STACKED.iter().copied().map(rect).collect();
+        assert_eq!(spatial_order(&screens), vec![2, 0, 3, 1]);
+
+        let context = |active: usize| ScreenContext {
+            active_display: DisplayId(active as u64),
+            displays: screens
+                .iter()
+                .enumerate()
+                .map(|(index, frame)| Display {
+                    id: DisplayId(index as u64),
+                    name: format!("Screen {}", index),
+                    frame: *frame,
+                    reserved: Insets::ZERO,
+                    scale_factor: 1.0,
+                    rotation: 0,
+                    is_primary: index == 0,
+                })
+                .collect(),
+        };
+        let target = |action, active| match plan(action, &context(active), screens[active]) {
+            Plan::MoveToDisplay { display, .. } => Some(display),
+            _ => None,
+        };
+
+        // Not the next one in the list
+        assert_eq!(target(Action::NextDisplay, 0), Some(DisplayId(3)));
+        assert_eq!(target(Action::PreviousDisplay, 0), Some(DisplayId(2)));
+        // Wrapping around from the rightmost and the leftmost ones
+        assert_eq!(target(Action::NextDisplay, 1), Some(DisplayId(2)));
+        assert_eq!(target(Action::PreviousDisplay, 2), Some(DisplayId(1)));
+    }
+
+    #[test]
+    fn reserved_space_shrinks_the_work_area() {
+        let config = Config {
+            reserved: vec![
+                // The 1367x769 screen
+                ReservedArea {
+                    display: DisplayMatch::Resolution(Size::new(1367.0, 769.0)),
+                    insets: Insets::new(30.0, 0.0, 0.0, 0.0),
+                },
+                // The one right of the first screen
+                ReservedArea {
+                    display: DisplayMatch::Id(DisplayId(3)),
+                    insets: Insets::new(0.0, 0.0, 100.0, 50.0),
+                },
+            ],
+            ..Config::default()
+        };
+        let plan_with_config = |action, active, window| {
+            plan_with(
+                action,
+                &context(active),
+                window,
+                None,
+                &config,
+                &ActionOptions::default(),
+                0,
+            )
+        };
+        let window = rect([100.0, 100.0, 400.0, 300.0]);
+
+        assert_eq!(
+            plan_with_config(Action::Maximize, 1, window),
+            Plan::Frame(rect([0.0, 30.0, 1367.0, 739.0]))
+        );
+        assert_eq!(
+            plan_with_config(Action::LeftHalf, 1, window),
+            Plan::Frame(rect([0.0, 30.0, 683.5, 739.0]))
+        );
+        // Filling the target without the space reserved there
+        let options = ActionOptions {
+            display_move: DisplayMove::Maximize,
+            ..ActionOptions::default()
+        };
+        assert_eq!(
+            plan_with(
+                Action::DisplayRight,
+                &context(0),
+                window,
+                None,
+                &config,
+                &options,
+                0
+            ),
+            Plan::MoveToDisplay {
+                display: DisplayId(3),
+                frame: rect([1440.0, 0.0, 1870.0, 980.0]),
+            }
+        );
+    }
+
+    #[test]
+    fn move_to_edges() {
+        // (0, 25) 1440x875
+        let context = context(0);
+        let window = rect([100.0, 50.0, 400.0, 300.0]);
+
+        // Only the origin changes, the size is kept
+        assert_eq!(
+            plan(Action::MoveToTopEdge, &context, window),
+            Plan::Move(Point::new(100.0, 25.0))
+        );
+        assert_eq!(
+            plan(Action::MoveToBottomEdge, &context, window),
+            Plan::Move(Point::new(100.0, 600.0))
+        );
+        assert_eq!(
+            plan(Action::MoveToLeftEdge, &context, window),
+            Plan::Move(Point::new(0.0, 50.0))
+        );
+        assert_eq!(
+            plan(Action::MoveToRightEdge, &context, window),
+            Plan::Move(Point::new(1040.0, 50.0))
+        );
+        // On a screen not at the origin, with space reserved on the left
+        assert_eq!(
+            plan(
+                Action::MoveToLeftEdge,
+                &self::context(4),
+                rect([-1000.0, 400.0, 400.0, 300.0])
+            ),
+            Plan::Move(Point::new(-1874.0, 400.0))
+        );
+    }
+
+    #[test]
+    fn grow_and_shrink() {
+        // (0, 25) 1440x875
+        let context = context(0);
+        let plan_with_options = |action, window, min_size, options: &ActionOptions| {
+            plan_with(
+                action,
+                &context,
+                window,
+                min_size,
+                &Config::default(),
+                options,
+                0,
+            )
+        };
+        let defaults = ActionOptions::default();
+        let window = rect([100.0, 50.0, 400.0, 300.0]);
+
+        // By the 20px step, only the edge moves
+        assert_eq!(
+            plan_with_options(Action::GrowLeft, window, None, &defaults),
+            Plan::Frame(rect([80.0, 50.0, 420.0, 300.0]))
+        );
+        assert_eq!(
+            plan_with_options(Action::ShrinkDown, window, None, &defaults),
+            Plan::Frame(rect([100.0, 50.0, 400.0, 280.0]))
+        );
+        // Up to the screen edges
+        let near_the_edges = rect([10.0, 30.0, 1420.0, 860.0]);
+        assert_eq!(
+            plan_with_options(Action::GrowLeft, near_the_edges, None, &defaults),
+            Plan::Frame(rect([0.0, 30.0, 1430.0, 860.0]))
+        );
+        assert_eq!(
+            plan_with_options(Action::GrowUp, near_the_edges, None, &defaults),
+            Plan::Frame(rect([10.0, 25.0, 1420.0, 865.0]))
+        );
+        assert_eq!(
+            plan_with_options(Action::GrowRight, near_the_edges, None, &defaults),
+            Plan::Frame(rect([10.0, 30.0, 1430.0, 860.0]))
+        );
+        assert_eq!(
+            plan_with_options(Action::GrowDown, near_the_edges, None, &defaults),
+            Plan::Frame(rect([10.0, 30.0, 1420.0, 870.0]))
+        );
+        // Down to the minimum size
+        let min_size = Some(Size::new(390.0, 290.0));
+        assert_eq!(
+            plan_with_options(Action::ShrinkRight, window, min_size, &defaults),
+            Plan::Frame(rect([100.0, 50.0, 390.0, 300.0]))
+        );
+        assert_eq!(
+            plan_with_options(Action::ShrinkUp, window, min_size, &defaults),
+            Plan::Frame(rect([100.0, 60.0, 400.0, 290.0]))
+        );
+        // By the step of the options
+        let options = ActionOptions {
+            resize_step: Step::Pixels(50.0),
+            ..ActionOptions::default()
+        };
+        assert_eq!(
+            plan_with_options(Action::GrowRight, window, None, &options),
+            Plan::Frame(rect([100.0, 50.0, 450.0, 300.0]))
+        );
+        // 10% of the screen height
+        let options = ActionOptions {
+            resize_step: Step::Percent(10.0),
+            ..ActionOptions::default()
+        };
+        assert_eq!(
+            plan_with_options(Action::GrowDown, window, None, &options),
+            Plan::Frame(rect([100.0, 50.0, 400.0, 387.5]))
+        );
+    }
+
+    #[test]
+    fn gaps() {
+        let gaps = Gaps {
+            inner: 8.0,
+            outer: Insets::new(10.0, 20.0, 30.0, 40.0),
+        };
+        let config = Config {
+            // Only on the 1920x1080 screen
+            display_gaps: [(DisplayId(3), gaps)].into_iter().collect(),
+            ..Config::default()
+        };
+        // (1460, 10) 1860x1040 once the outer gaps are taken
+        let context = context(3);
+        let window = rect([1500.0, 100.0, 400.0, 300.0]);
+        let plan_with_gaps = |action| {
+            plan_with(
+                action,
+                &context,
+                window,
+                None,
+                &config,
+                &ActionOptions::default(),
+                0,
+            )
+        };
+
+        assert_eq!(
+            plan_with_gaps(Action::Maximize),
+            Plan::Frame(rect([1460.0, 10.0, 1860.0, 1040.0]))
+        );
+        // Half the inner gap on the edges shared with another snap
+        assert_eq!(
+            plan_with_gaps(Action::LeftHalf),
+            Plan::Frame(rect([1460.0, 10.0, 926.0, 1040.0]))
+        );
+        assert_eq!(
+            plan_with_gaps(Action::RightHalf),
+            Plan::Frame(rect([2394.0, 10.0, 926.0, 1040.0]))
+        );
+        assert_eq!(
+            plan_with_gaps(Action::TopHalf),
+            Plan::Frame(rect([1460.0, 10.0, 1860.0, 516.0]))
+        );
+        assert_eq!(
+            plan_with_gaps(Action::FirstThird),
+            Plan::Frame(rect([1460.0, 10.0, 616.0, 1040.0]))
+        );
+        assert_eq!(
+            plan_with_gaps(Action::CenterThird),
+            Plan::Frame(rect([2084.0, 10.0, 612.0, 1040.0]))
+        );
+        assert_eq!(
+            plan_with_gaps(Action::TopLeftQuarter),
+            Plan::Frame(rect([1460.0, 10.0, 926.0, 516.0]))
+        );
+        assert_eq!(
+            plan_with_gaps(Action::BottomRightQuarter),
+            Plan::Frame(rect([2394.0, 534.0, 926.0, 516.0]))
+        );
+        // Only the outer ones, as it has no neighbor
+        assert_eq!(
+            plan_with_gaps(Action::AlmostMaximize),
+            Plan::Frame(rect([1646.0, 114.0, 1488.0, 832.0]))
+        );
+        // Not a snap
+        assert_eq!(
+            plan_with_gaps(Action::Center),
+            plan(Action::Center, &context, window)
+        );
+        // The other screens have no gaps
+        assert_eq!(
+            plan_with(
+                Action::LeftHalf,
+                &self::context(1),
+                window,
+                None,
+                &config,
+                &ActionOptions::default(),
+                0
+            ),
+            plan(Action::LeftHalf, &self::context(1), window)
+        );
+    }
+
+    #[test]
+    fn unknown_active_display() {
+        let context = context(SCREENS.len());
+        let window = rect([0.0, 0.0, 100.0, 100.0]);
+
+        assert_eq!(plan(Action::LeftHalf, &context, window), Plan::Noop);
+    }
+}
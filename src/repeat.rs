@@ -0,0 +1,169 @@
+//! The last action done by [`apply_to_focused_window()`], to tell when the
+//! same action is done again, which cycles the size, see [`CycleSizes`], or
+//! accelerates the steps, see [`ActionOptions::acceleration`].
+//!
+//! [`apply_to_focused_window()`]: crate::apply_to_focused_window
+//! [`CycleSizes`]: crate::config::CycleSizes
+//! [`ActionOptions::acceleration`]: crate::actions::ActionOptions::acceleration
+
+use std::cell::Cell;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::actions::Action;
+use crate::backend::WindowId;
+use crate::geometry::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LastAction {
+    window: WindowId,
+    action: Action,
+    /// The frame after the action.
+    frame: Rect,
+    /// How many times it had been repeated.
+    repeat: usize,
+    /// When it was done.
+    at: Instant,
+}
+
+thread_local! {
+    /// Like the frame history, every thread has its own last action.
+    static LAST_ACTION: Cell<Option<LastAction>> = const { Cell::new(None) };
+}
+
+/// How many times in a row `action` has been done to `window`, which is at
+/// `current_frame`, as of `now`, 0 if this is the first time.
+///
+/// The streak breaks once the window gets moved or resized by something else.
+/// The streak of a nudge or a resize also breaks once `timeout` has passed
+/// since the last time, snap sizes keep cycling however late the next press
+/// is, like Rectangle.
+pub(crate) fn repeat_count(
+    window: WindowId,
+    action: Action,
+    current_frame: Rect,
+    now: Instant,
+    timeout: Duration,
+) -> usize {
+    match LAST_ACTION.get() {
+        Some(last)
+            if last.window == window
+                && last.action == action
+                && last.frame == current_frame
+                && (!accelerates(action) || now.saturating_duration_since(last.at) <= timeout) =>
+        {
+            last.repeat + 1
+        }
+        _ => 0,
+    }
+}
+
+/// Whether the step of `action` accelerates, see [`ActionOptions::acceleration`].
+fn accelerates(action: Action) -> bool {
+    matches!(
+        action,
+        Action::MakeLarger
+            | Action::MakeSmaller
+            | Action::GrowLeft
+            | Action::GrowRight
+            | Action::GrowUp
+            | Action::GrowDown
+            | Action::ShrinkLeft
+            | Action::ShrinkRight
+            | Action::ShrinkUp
+            | Action::ShrinkDown
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::MoveLeft
+            | Action::MoveRight
+    )
+}
+
+/// Record that `action` was done to `window` for the `repeat`th time in a row
+/// at `at`, leaving it at `frame`.
+pub(crate) fn record(window: WindowId, action: Action, frame: Rect, repeat: usize, at: Instant) {
+    LAST_ACTION.set(Some(LastAction {
+        window,
+        action,
+        frame,
+        repeat,
+        at,
+    }));
+}
+
+/// Break the streak, e.g., after an action that does not change the frame.
+pub(crate) fn reset() {
+    LAST_ACTION.set(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::geometry::Size;
+
+    const WINDOW: WindowId = WindowId(1);
+    const FRAME: Rect = Rect::ZERO;
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn continues_within_the_timeout() {
+        let start = Instant::now();
+        record(WINDOW, Action::MoveLeft, FRAME, 0, start);
+
+        let now = start + Duration::from_millis(200);
+        assert_eq!(
+            repeat_count(WINDOW, Action::MoveLeft, FRAME, now, TIMEOUT),
+            1
+        );
+        record(WINDOW, Action::MoveLeft, FRAME, 1, now);
+
+        // From the last time, not the first one
+        let now = now + Duration::from_millis(400);
+        assert_eq!(
+            repeat_count(WINDOW, Action::MoveLeft, FRAME, now, TIMEOUT),
+            2
+        );
+    }
+
+    #[test]
+    fn resets_after_the_timeout() {
+        let start = Instant::now();
+        record(WINDOW, Action::MoveLeft, FRAME, 3, start);
+
+        let now = start + Duration::from_millis(501);
+        assert_eq!(
+            repeat_count(WINDOW, Action::MoveLeft, FRAME, now, TIMEOUT),
+            0
+        );
+    }
+
+    #[test]
+    fn cycles_without_a_timeout() {
+        let start = Instant::now();
+        record(WINDOW, Action::LeftHalf, FRAME, 1, start);
+
+        let now = start + Duration::from_secs(60);
+        assert_eq!(
+            repeat_count(WINDOW, Action::LeftHalf, FRAME, now, TIMEOUT),
+            2
+        );
+        // Unless the window has been moved since
+        let moved = Rect::new(FRAME.origin, Size::new(1.0, 1.0));
+        assert_eq!(
+            repeat_count(WINDOW, Action::LeftHalf, moved, now, TIMEOUT),
+            0
+        );
+    }
+
+    #[test]
+    fn resets_on_another_action() {
+        let start = Instant::now();
+        record(WINDOW, Action::LeftHalf, FRAME, 0, start);
+
+        assert_eq!(
+            repeat_count(WINDOW, Action::RightHalf, FRAME, start, TIMEOUT),
+            0
+        );
+    }
+}
@@ -0,0 +1,535 @@
+//! Drive [`apply_to_focused_window()`], [`undo()`] and [`redo()`] on a
+//! [`SimulatedDesktop`] and check where the window ends up.
+
+use std::time::Duration;
+
+use libwmgr::actions::Acceleration;
+use libwmgr::actions::Action;
+use libwmgr::actions::ActionOptions;
+use libwmgr::actions::Length;
+use libwmgr::actions::Position;
+use libwmgr::apply_to_focused_window;
+use libwmgr::backend::simulated::SimulatedDesktop;
+use libwmgr::backend::simulated::SimulatedScreen;
+use libwmgr::backend::simulated::SimulatedWindow;
+use libwmgr::backend::WindowId;
+use libwmgr::error::Error;
+use libwmgr::geometry::Point;
+use libwmgr::geometry::Rect;
+use libwmgr::geometry::Size;
+use libwmgr::redo;
+use libwmgr::undo;
+
+fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+    Rect::new(Point::new(x, y), Size::new(width, height))
+}
+
+/// A 1200x900 main screen with a 25px menu bar, and a 2400x1750 screen on its
+/// right, i.e., twice as large as the main screen's visible frame, with a
+/// 400x300 window at (100, 100) on the main screen.
+fn desktop() -> (SimulatedDesktop, WindowId) {
+    let mut desktop = SimulatedDesktop::new(vec![
+        SimulatedScreen::with_visible_frame(
+            rect(0.0, 0.0, 1200.0, 900.0),
+            rect(0.0, 25.0, 1200.0, 875.0),
+        ),
+        SimulatedScreen::new(rect(1200.0, 0.0, 2400.0, 1750.0)),
+    ]);
+    let window = desktop.add_window(SimulatedWindow::new(rect(100.0, 100.0, 400.0, 300.0)));
+
+    (desktop, window)
+}
+
+fn apply(desktop: &mut SimulatedDesktop, action: Action) {
+    apply_to_focused_window(desktop, action, &ActionOptions::default()).unwrap();
+}
+
+fn frame(desktop: &SimulatedDesktop, window: WindowId) -> Rect {
+    desktop.window(window).unwrap().frame
+}
+
+#[test]
+fn half() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::LeftHalf);
+    assert_eq!(frame(&desktop, window), rect(0.0, 25.0, 600.0, 875.0));
+
+    apply(&mut desktop, Action::BottomHalf);
+    assert_eq!(frame(&desktop, window), rect(0.0, 462.5, 1200.0, 437.5));
+}
+
+#[test]
+fn corner() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::TopRightQuarter);
+    assert_eq!(frame(&desktop, window), rect(600.0, 25.0, 600.0, 437.5));
+}
+
+#[test]
+fn center() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::Center);
+    assert_eq!(frame(&desktop, window), rect(400.0, 312.5, 400.0, 300.0));
+}
+
+#[test]
+fn maximize() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::Maximize);
+    assert_eq!(frame(&desktop, window), rect(0.0, 25.0, 1200.0, 875.0));
+}
+
+#[test]
+fn next_display() {
+    let (mut desktop, window) = desktop();
+
+    // Scaled by 2 relative to the visible frames
+    apply(&mut desktop, Action::NextDisplay);
+    assert_eq!(frame(&desktop, window), rect(1400.0, 150.0, 800.0, 600.0));
+
+    // And back
+    apply(&mut desktop, Action::NextDisplay);
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+}
+
+#[test]
+fn workspace() {
+    let (mut desktop, window) = desktop();
+    desktop.set_workspace_count(2);
+
+    apply(&mut desktop, Action::NextDesktop);
+    assert_eq!(desktop.window(window).unwrap().workspace, 2);
+    assert_eq!(desktop.current_workspace(), 2);
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+
+    // Already the last one
+    apply(&mut desktop, Action::NextDesktop);
+    assert_eq!(desktop.window(window).unwrap().workspace, 2);
+
+    apply(&mut desktop, Action::PreviousDesktop);
+    assert_eq!(desktop.window(window).unwrap().workspace, 1);
+    assert_eq!(desktop.current_workspace(), 1);
+}
+
+#[test]
+fn fullscreen() {
+    let (mut desktop, window) = desktop();
+
+    // The whole screen, including the menu bar
+    apply(&mut desktop, Action::ToggleFullscreen);
+    assert!(desktop.window(window).unwrap().is_fullscreen());
+    assert_eq!(frame(&desktop, window), rect(0.0, 0.0, 1200.0, 900.0));
+
+    apply(&mut desktop, Action::ToggleFullscreen);
+    assert!(!desktop.window(window).unwrap().is_fullscreen());
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+}
+
+#[test]
+fn cycle_however_late() {
+    let (mut desktop, window) = desktop();
+    let options = ActionOptions {
+        repeat_timeout: Duration::from_millis(50),
+        ..Default::default()
+    };
+
+    apply_to_focused_window(&mut desktop, Action::LeftHalf, &options).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    // From 1/2 to 2/3
+    apply_to_focused_window(&mut desktop, Action::LeftHalf, &options).unwrap();
+    assert_eq!(frame(&desktop, window), rect(0.0, 25.0, 800.0, 875.0));
+}
+
+#[test]
+fn accelerate_until_the_timeout() {
+    let (mut desktop, window) = desktop();
+    let options = ActionOptions {
+        acceleration: Acceleration::Linear {
+            increment: 1.0,
+            max: 4.0,
+        },
+        repeat_timeout: Duration::from_millis(50),
+        ..Default::default()
+    };
+
+    apply_to_focused_window(&mut desktop, Action::MoveRight, &options).unwrap();
+    assert_eq!(frame(&desktop, window).origin.x, 110.0);
+    // Right away, twice the step
+    apply_to_focused_window(&mut desktop, Action::MoveRight, &options).unwrap();
+    assert_eq!(frame(&desktop, window).origin.x, 130.0);
+
+    // Too late, back to the step
+    std::thread::sleep(Duration::from_millis(100));
+    apply_to_focused_window(&mut desktop, Action::MoveRight, &options).unwrap();
+    assert_eq!(frame(&desktop, window).origin.x, 140.0);
+}
+
+#[test]
+fn undo_and_redo_a_frame_change() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::LeftHalf);
+    apply(&mut desktop, Action::Maximize);
+
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(0.0, 25.0, 600.0, 875.0));
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+    assert!(!undo(&mut desktop).unwrap());
+
+    assert!(redo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(0.0, 25.0, 600.0, 875.0));
+    assert!(redo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(0.0, 25.0, 1200.0, 875.0));
+    assert!(!redo(&mut desktop).unwrap());
+}
+
+#[test]
+fn undo_and_redo_a_display_move() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::NextDisplay);
+
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+    assert!(redo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(1400.0, 150.0, 800.0, 600.0));
+}
+
+#[test]
+fn undo_and_redo_a_workspace_move() {
+    let (mut desktop, window) = desktop();
+    desktop.set_workspace_count(3);
+
+    apply(&mut desktop, Action::NextDesktop);
+    apply(&mut desktop, Action::NextDesktop);
+    assert_eq!(desktop.window(window).unwrap().workspace, 3);
+
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(desktop.window(window).unwrap().workspace, 2);
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(desktop.window(window).unwrap().workspace, 1);
+    assert_eq!(desktop.current_workspace(), 1);
+
+    assert!(redo(&mut desktop).unwrap());
+    assert_eq!(desktop.window(window).unwrap().workspace, 2);
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+}
+
+#[test]
+fn undo_skips_closed_windows() {
+    let (mut desktop, first) = desktop();
+    apply(&mut desktop, Action::LeftHalf);
+    let second = desktop.add_window(SimulatedWindow::new(rect(200.0, 200.0, 400.0, 300.0)));
+    apply(&mut desktop, Action::RightHalf);
+
+    desktop.close_window(second);
+
+    // Focuses the first window to undo its change
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(desktop.focused_window(), Some(first));
+    assert_eq!(frame(&desktop, first), rect(100.0, 100.0, 400.0, 300.0));
+    assert!(!undo(&mut desktop).unwrap());
+}
+
+#[test]
+fn new_action_clears_redo() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::LeftHalf);
+    assert!(undo(&mut desktop).unwrap());
+    apply(&mut desktop, Action::RightHalf);
+
+    assert!(!redo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(600.0, 25.0, 600.0, 875.0));
+    // The new one can still be undone
+    assert!(undo(&mut desktop).unwrap());
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+}
+
+#[test]
+fn failed_replay_is_kept() {
+    let (mut desktop, window) = desktop();
+    desktop.set_workspace_count(2);
+
+    apply(&mut desktop, Action::NextDesktop);
+    assert!(undo(&mut desktop).unwrap());
+
+    // Workspace 2 is gone, it cannot be redone for now
+    desktop.set_workspace_count(1);
+    assert!(matches!(redo(&mut desktop), Err(Error::NoSuchWorkspace)));
+
+    desktop.set_workspace_count(2);
+    assert!(redo(&mut desktop).unwrap());
+    assert_eq!(desktop.window(window).unwrap().workspace, 2);
+}
+
+#[test]
+fn restore() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::LeftHalf);
+    apply(&mut desktop, Action::Maximize);
+    // From before the first action
+    apply(&mut desktop, Action::Restore);
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+
+    // Nothing left to restore
+    apply(&mut desktop, Action::Restore);
+    assert_eq!(frame(&desktop, window), rect(100.0, 100.0, 400.0, 300.0));
+}
+
+#[test]
+fn restore_after_a_manual_move() {
+    let (mut desktop, window) = desktop();
+
+    apply(&mut desktop, Action::LeftHalf);
+    desktop.window_mut(window).unwrap().frame = rect(50.0, 60.0, 300.0, 200.0);
+    apply(&mut desktop, Action::RightHalf);
+
+    apply(&mut desktop, Action::Restore);
+    assert_eq!(frame(&desktop, window), rect(50.0, 60.0, 300.0, 200.0));
+}
+
+#[test]
+fn restore_an_untouched_window() {
+    let (mut desktop, first) = desktop();
+    apply(&mut desktop, Action::LeftHalf);
+    let second = desktop.add_window(SimulatedWindow::new(rect(200.0, 200.0, 400.0, 300.0)));
+
+    apply(&mut desktop, Action::Restore);
+    assert_eq!(frame(&desktop, second), rect(200.0, 200.0, 400.0, 300.0));
+    assert_eq!(frame(&desktop, first), rect(0.0, 25.0, 600.0, 875.0));
+}
+
+/// What an action does to the window in [`every_action()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Effect {
+    /// It ends up at this frame, on the same workspace.
+    Frame(Rect),
+    /// It moves to this workspace, keeping its frame.
+    Workspace(usize),
+    /// It fills the whole main screen.
+    Fullscreen,
+}
+
+/// Every action, [`expected()`] stops compiling when one is added.
+const EVERY_ACTION: &[Action] = &[
+    Action::TopHalf,
+    Action::BottomHalf,
+    Action::LeftHalf,
+    Action::RightHalf,
+    Action::CenterHalf,
+    Action::TopLeftQuarter,
+    Action::TopRightQuarter,
+    Action::BottomLeftQuarter,
+    Action::BottomRightQuarter,
+    Action::TopLeftSixth,
+    Action::TopCenterSixth,
+    Action::TopRightSixth,
+    Action::BottomLeftSixth,
+    Action::BottomCenterSixth,
+    Action::BottomRightSixth,
+    Action::TopThird,
+    Action::MiddleThird,
+    Action::BottomThird,
+    Action::Center,
+    Action::FirstFourth,
+    Action::SecondFourth,
+    Action::ThirdFourth,
+    Action::LastFourth,
+    Action::FirstThird,
+    Action::CenterThird,
+    Action::LastThird,
+    Action::FirstTwoThirds,
+    Action::CenterTwoThirds,
+    Action::LastTwoThirds,
+    Action::FirstThreeFourths,
+    Action::CenterThreeFourths,
+    Action::LastThreeFourths,
+    Action::TopThreeFourths,
+    Action::BottomThreeFourths,
+    Action::TopTwoThirds,
+    Action::BottomTwoThirds,
+    Action::TopCenterTwoThirds,
+    Action::TopFirstFourth,
+    Action::TopSecondFourth,
+    Action::TopThirdFourth,
+    Action::TopLastFourth,
+    Action::Grid {
+        columns: 2,
+        rows: 2,
+        x: 1,
+        y: 1,
+        width: 1,
+        height: 1,
+    },
+    Action::Place {
+        x: Position::End,
+        y: Position::Start,
+        width: Length::Pixels(500.0),
+        height: Length::Fraction(0.5),
+    },
+    Action::ResizeTo {
+        width: Length::Pixels(600.0),
+        height: Length::Pixels(400.0),
+    },
+    Action::MakeLarger,
+    Action::MakeSmaller,
+    Action::GrowLeft,
+    Action::GrowRight,
+    Action::GrowUp,
+    Action::GrowDown,
+    Action::ShrinkLeft,
+    Action::ShrinkRight,
+    Action::ShrinkUp,
+    Action::ShrinkDown,
+    Action::AlmostMaximize,
+    Action::Maximize,
+    Action::MaximizeWidth,
+    Action::MaximizeHeight,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveToTopEdge,
+    Action::MoveToBottomEdge,
+    Action::MoveToLeftEdge,
+    Action::MoveToRightEdge,
+    Action::NextDesktop,
+    Action::PreviousDesktop,
+    Action::NextDisplay,
+    Action::PreviousDisplay,
+    Action::DisplayLeft,
+    Action::DisplayRight,
+    Action::DisplayAbove,
+    Action::DisplayBelow,
+    Action::Restore,
+    Action::ToggleFullscreen,
+];
+
+/// What `action` does to the 400x300 window at (100, 100) of [`desktop()`],
+/// when it is on the second of 3 workspaces.
+fn expected(action: Action) -> Effect {
+    // The visible frame of the main screen
+    let (w, h) = (1200.0, 875.0);
+    let on_next_display = Effect::Frame(rect(1400.0, 150.0, 800.0, 600.0));
+    let unchanged = Effect::Frame(rect(100.0, 100.0, 400.0, 300.0));
+
+    match action {
+        Action::TopHalf => Effect::Frame(rect(0.0, 25.0, w, h / 2.0)),
+        Action::BottomHalf => Effect::Frame(rect(0.0, 462.5, w, h / 2.0)),
+        Action::LeftHalf => Effect::Frame(rect(0.0, 25.0, 600.0, h)),
+        Action::RightHalf => Effect::Frame(rect(600.0, 25.0, 600.0, h)),
+        Action::CenterHalf => Effect::Frame(rect(300.0, 25.0, 600.0, h)),
+        Action::TopLeftQuarter => Effect::Frame(rect(0.0, 25.0, 600.0, 437.5)),
+        Action::TopRightQuarter => Effect::Frame(rect(600.0, 25.0, 600.0, 437.5)),
+        Action::BottomLeftQuarter => Effect::Frame(rect(0.0, 462.5, 600.0, 437.5)),
+        Action::BottomRightQuarter => Effect::Frame(rect(600.0, 462.5, 600.0, 437.5)),
+        Action::TopLeftSixth => Effect::Frame(rect(0.0, 25.0, 400.0, 437.5)),
+        Action::TopCenterSixth => Effect::Frame(rect(400.0, 25.0, 400.0, 437.5)),
+        Action::TopRightSixth => Effect::Frame(rect(800.0, 25.0, 400.0, 437.5)),
+        Action::BottomLeftSixth => Effect::Frame(rect(0.0, 462.5, 400.0, 437.5)),
+        Action::BottomCenterSixth => Effect::Frame(rect(400.0, 462.5, 400.0, 437.5)),
+        Action::BottomRightSixth => Effect::Frame(rect(800.0, 462.5, 400.0, 437.5)),
+        Action::TopThird => Effect::Frame(rect(0.0, 25.0, w, h / 3.0)),
+        Action::MiddleThird => Effect::Frame(rect(0.0, 25.0 + h / 3.0, w, h / 3.0)),
+        Action::BottomThird => Effect::Frame(rect(0.0, 25.0 + h * 2.0 / 3.0, w, h / 3.0)),
+        Action::Center => Effect::Frame(rect(400.0, 312.5, 400.0, 300.0)),
+        Action::FirstFourth => Effect::Frame(rect(0.0, 25.0, 300.0, h)),
+        Action::SecondFourth => Effect::Frame(rect(300.0, 25.0, 300.0, h)),
+        Action::ThirdFourth => Effect::Frame(rect(600.0, 25.0, 300.0, h)),
+        Action::LastFourth => Effect::Frame(rect(900.0, 25.0, 300.0, h)),
+        Action::FirstThird => Effect::Frame(rect(0.0, 25.0, 400.0, h)),
+        Action::CenterThird => Effect::Frame(rect(400.0, 25.0, 400.0, h)),
+        Action::LastThird => Effect::Frame(rect(800.0, 25.0, 400.0, h)),
+        Action::FirstTwoThirds => Effect::Frame(rect(0.0, 25.0, 800.0, h)),
+        Action::CenterTwoThirds => Effect::Frame(rect(200.0, 25.0, 800.0, h)),
+        Action::LastTwoThirds => Effect::Frame(rect(400.0, 25.0, 800.0, h)),
+        Action::FirstThreeFourths => Effect::Frame(rect(0.0, 25.0, 900.0, h)),
+        Action::CenterThreeFourths => Effect::Frame(rect(150.0, 25.0, 900.0, h)),
+        Action::LastThreeFourths => Effect::Frame(rect(300.0, 25.0, 900.0, h)),
+        Action::TopThreeFourths => Effect::Frame(rect(0.0, 25.0, w, 656.25)),
+        Action::BottomThreeFourths => Effect::Frame(rect(0.0, 243.75, w, 656.25)),
+        Action::TopTwoThirds => Effect::Frame(rect(0.0, 25.0, w, h * 2.0 / 3.0)),
+        Action::BottomTwoThirds => Effect::Frame(rect(0.0, 25.0 + h / 3.0, w, h * 2.0 / 3.0)),
+        Action::TopCenterTwoThirds => Effect::Frame(rect(200.0, 25.0, 800.0, h * 2.0 / 3.0)),
+        Action::TopFirstFourth => Effect::Frame(rect(0.0, 25.0, w, 218.75)),
+        Action::TopSecondFourth => Effect::Frame(rect(0.0, 243.75, w, 218.75)),
+        Action::TopThirdFourth => Effect::Frame(rect(0.0, 462.5, w, 218.75)),
+        Action::TopLastFourth => Effect::Frame(rect(0.0, 681.25, w, 218.75)),
+        // The bottom right cell of 2x2
+        Action::Grid { .. } => Effect::Frame(rect(600.0, 462.5, 600.0, 437.5)),
+        // Against the right edge, 500px wide, half as tall as the screen
+        Action::Place { .. } => Effect::Frame(rect(700.0, 25.0, 500.0, 437.5)),
+        // Around the same center
+        Action::ResizeTo { .. } => Effect::Frame(rect(0.0, 50.0, 600.0, 400.0)),
+        // 20px wider, keeping the aspect ratio
+        Action::MakeLarger => Effect::Frame(rect(90.0, 92.5, 420.0, 315.0)),
+        Action::MakeSmaller => Effect::Frame(rect(110.0, 107.5, 380.0, 285.0)),
+        Action::GrowLeft => Effect::Frame(rect(80.0, 100.0, 420.0, 300.0)),
+        Action::GrowRight => Effect::Frame(rect(100.0, 100.0, 420.0, 300.0)),
+        Action::GrowUp => Effect::Frame(rect(100.0, 80.0, 400.0, 320.0)),
+        Action::GrowDown => Effect::Frame(rect(100.0, 100.0, 400.0, 320.0)),
+        Action::ShrinkLeft => Effect::Frame(rect(120.0, 100.0, 380.0, 300.0)),
+        Action::ShrinkRight => Effect::Frame(rect(100.0, 100.0, 380.0, 300.0)),
+        Action::ShrinkUp => Effect::Frame(rect(100.0, 120.0, 400.0, 280.0)),
+        Action::ShrinkDown => Effect::Frame(rect(100.0, 100.0, 400.0, 280.0)),
+        Action::AlmostMaximize => Effect::Frame(rect(w * 0.1, 25.0 + h * 0.1, w * 0.8, h * 0.8)),
+        Action::Maximize => Effect::Frame(rect(0.0, 25.0, w, h)),
+        Action::MaximizeWidth => Effect::Frame(rect(0.0, 100.0, w, 300.0)),
+        Action::MaximizeHeight => Effect::Frame(rect(100.0, 25.0, 400.0, h)),
+        // By the 10px step
+        Action::MoveUp => Effect::Frame(rect(100.0, 90.0, 400.0, 300.0)),
+        Action::MoveDown => Effect::Frame(rect(100.0, 110.0, 400.0, 300.0)),
+        Action::MoveLeft => Effect::Frame(rect(90.0, 100.0, 400.0, 300.0)),
+        Action::MoveRight => Effect::Frame(rect(110.0, 100.0, 400.0, 300.0)),
+        Action::MoveToTopEdge => Effect::Frame(rect(100.0, 25.0, 400.0, 300.0)),
+        Action::MoveToBottomEdge => Effect::Frame(rect(100.0, 600.0, 400.0, 300.0)),
+        Action::MoveToLeftEdge => Effect::Frame(rect(0.0, 100.0, 400.0, 300.0)),
+        Action::MoveToRightEdge => Effect::Frame(rect(800.0, 100.0, 400.0, 300.0)),
+        Action::NextDesktop => Effect::Workspace(3),
+        Action::PreviousDesktop => Effect::Workspace(1),
+        // With 2 displays, the other one is both the next and the previous
+        Action::NextDisplay | Action::PreviousDisplay => on_next_display,
+        // Its center is to the right of and below the main screen's
+        Action::DisplayRight | Action::DisplayBelow => on_next_display,
+        Action::DisplayLeft | Action::DisplayAbove => unchanged,
+        // Never touched
+        Action::Restore => unchanged,
+        Action::ToggleFullscreen => Effect::Fullscreen,
+    }
+}
+
+#[test]
+fn every_action() {
+    for &action in EVERY_ACTION {
+        let (mut desktop, window) = desktop();
+        desktop.set_workspace_count(3);
+        desktop.window_mut(window).unwrap().workspace = 2;
+        desktop.set_focus(window);
+
+        apply(&mut desktop, action);
+
+        let simulated = desktop.window(window).unwrap();
+        match expected(action) {
+            Effect::Frame(frame) => {
+                assert_eq!(simulated.frame, frame, "{:?}", action);
+                assert_eq!(simulated.workspace, 2, "{:?}", action);
+            }
+            Effect::Workspace(workspace) => {
+                assert_eq!(simulated.workspace, workspace, "{:?}", action);
+                assert_eq!(desktop.current_workspace(), workspace, "{:?}", action);
+                assert_eq!(simulated.frame, rect(100.0, 100.0, 400.0, 300.0));
+            }
+            Effect::Fullscreen => {
+                assert!(simulated.is_fullscreen());
+                assert_eq!(simulated.frame, rect(0.0, 0.0, 1200.0, 900.0));
+            }
+        }
+    }
+}